@@ -1,9 +1,19 @@
 extern crate core;
 
-use clap::{arg, Parser, ValueEnum};
+use clap::{Parser, ValueEnum};
 use dotenv::dotenv;
+use impl_ethers_rs::airdrop::{self, AirdropResult};
+use impl_ethers_rs::batch_mint::{self, MintResult};
+use impl_ethers_rs::erc721::{
+    Erc721, Erc721Enumerable, Erc721Metadata, Erc721Mintable, Erc721Transferable, Nft721,
+    TransferableNft721,
+};
+use impl_ethers_rs::multi_sig_wallet::client::MultiSigEvent;
+use impl_ethers_rs::multi_sig_wallet::proposal::{decode_call, Proposal};
+use impl_ethers_rs::{telemetry, Account, Address};
 use prelude::*;
 use std::env;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 #[derive(ValueEnum, Clone, Debug)]
@@ -21,8 +31,46 @@ enum Command {
     UpdateTime,
     NftMarketSell,
     NftMarketCancel,
+    NftMarketCancelByAdmin,
     NftMarketBuy,
     ApproveForSell,
+    OwnerOf,
+    BalanceOf,
+    TokenUri,
+    TokensOfOwner,
+    IsOwner,
+    BatchMint,
+    Approve,
+    GetApproved,
+    SetApprovalForAll,
+    IsApprovedForAll,
+    ListApprovals,
+    /// Revokes the operator approvals of WALLET_SECRET and, when set, WALLET_SECRET_2
+    RevokeApprovals,
+    Inspect,
+    RoyaltyInfo,
+    TokenMetadata,
+    Snapshot,
+    Airdrop,
+    ChainlinkConfig,
+    SetChainlinkToken,
+    SetChainlinkFee,
+    SetOracleAddress,
+    SetTimeAdapterJobId,
+    CancelRequest,
+    WithdrawLink,
+    SetTimestampForDebug,
+    RevealStatus,
+    LinkStatus,
+    JobSpec,
+    MultiSigInfo,
+    MultiSigTransaction,
+    MultiSigPending,
+    MultiSigAddOwner,
+    MultiSigRemoveOwner,
+    MultiSigChangeRequirement,
+    MultiSigSubmit,
+    MultiSigConfirm,
 }
 
 #[derive(ValueEnum, Clone, Debug)]
@@ -33,6 +81,7 @@ pub enum Contract {
     RevealNft721,
     NftMarket,
     MetaTransactionWallet,
+    MultiSigWallet,
 }
 
 #[derive(Parser, Debug)]
@@ -70,14 +119,150 @@ struct Args {
     #[clap(value_enum)]
     contract: Contract,
 
-    #[arg(long, default_value_t = 0.1)]
-    ether: f64,
+    #[arg(long, default_value = "0.1")]
+    ether: String,
 
     #[arg(long, default_value = "0x0E91D6613a84d7C8b72a289D8b275AF7717C3d2E")]
     to_address: String,
 
-    #[arg(long, default_value_t = 1)]
-    token_id: u128,
+    #[arg(long, default_value = "1")]
+    token_id: String,
+
+    #[arg(long, value_delimiter = ',')]
+    token_ids: Vec<String>,
+
+    /// Per-id amounts for erc1155 transfers, matching --token-ids
+    #[arg(long, value_delimiter = ',')]
+    amounts: Vec<String>,
+
+    /// Hex calldata passed to safe transfers
+    #[arg(long, default_value = "")]
+    data: String,
+
+    /// Owner to transfer erc721 tokens from as an approved operator, defaults to our wallet
+    #[arg(long)]
+    from_address: Option<String>,
+
+    /// Use safeTransferFrom for erc721 transfers, or transferFrom when false
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    safe: bool,
+
+    /// Contract to operate on instead of the one configured for --contract
+    #[arg(long)]
+    address: Option<String>,
+
+    /// Market to trade on instead of the configured one
+    #[arg(long)]
+    market_address: Option<String>,
+
+    /// Collection of market orders, defaults to the one for --contract; required with
+    /// --contract nft-market
+    #[arg(long)]
+    nft_address: Option<String>,
+
+    /// MultiSigWallet to submit through instead of the configured one
+    #[arg(long)]
+    multi_sig_address: Option<String>,
+
+    /// Submit admin calls (mint, chainlink settings, update-time, market admin) to the
+    /// MultiSigWallet instead of sending them from our wallet; the wallet must own the target
+    /// contract. Other commands fail with it
+    #[arg(long, default_value_t = false, action = clap::ArgAction::Set)]
+    multi_sig: bool,
+
+    /// With link-status, send LINK_TOP_UP_AMOUNT from our wallet when the reveal contract
+    /// is low on LINK
+    #[arg(long, default_value_t = false, action = clap::ArgAction::Set)]
+    top_up: bool,
+
+    /// Defaults to WALLET_ADDRESS
+    #[arg(long)]
+    owner_address: Option<String>,
+
+    /// Defaults to the nft market
+    #[arg(long)]
+    operator_address: Option<String>,
+
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    approved: bool,
+
+    /// First block scanned for ApprovalForAll logs
+    #[arg(long, default_value_t = 0)]
+    from_block: u64,
+
+    /// Block to pin snapshot reads to, defaults to the latest block
+    #[arg(long)]
+    block: Option<u64>,
+
+    /// Snapshot file, written as CSV or as JSON Lines for .jsonl
+    #[arg(long, default_value = "snapshot.csv")]
+    output: PathBuf,
+
+    /// CSV (content_hash[,amount]) or JSON manifest for batch-mint
+    #[arg(long)]
+    manifest: Option<PathBuf>,
+
+    /// Defaults to <manifest>.progress.json
+    #[arg(long)]
+    progress: Option<PathBuf>,
+
+    #[arg(long, default_value_t = 4)]
+    concurrency: usize,
+
+    /// Recipients CSV (recipient,token_id,amount,content_hash) for airdrop
+    #[arg(long)]
+    recipients: Option<PathBuf>,
+
+    /// Transactions sent per second by airdrop
+    #[arg(long, default_value_t = 1.0)]
+    rate: f64,
+
+    /// LINK token address for set-chainlink-token
+    #[arg(long)]
+    link_address: Option<String>,
+
+    #[arg(long)]
+    oracle_address: Option<String>,
+
+    /// Chainlink job id, hyphens optional
+    #[arg(long)]
+    job_id: Option<String>,
+
+    /// Oracle fee per request in LINK
+    #[arg(long)]
+    fee: Option<String>,
+
+    /// Chainlink request id to cancel, as returned by update-time
+    #[arg(long)]
+    request_id: Option<String>,
+
+    /// Expiration of the request to cancel, in unix seconds
+    #[arg(long)]
+    expiration: Option<u64>,
+
+    /// Unix timestamp for set-timestamp-for-debug
+    #[arg(long)]
+    timestamp: Option<u64>,
+
+    /// MultiSigWallet transaction id
+    #[arg(long)]
+    transaction_id: Option<u64>,
+
+    /// Confirmations required by multi-sig-change-requirement
+    #[arg(long)]
+    required: Option<u64>,
+
+    /// Target of multi-sig-submit
+    #[arg(long)]
+    destination: Option<String>,
+
+    /// Ether sent along with multi-sig-submit
+    #[arg(long, default_value = "0")]
+    value: String,
+
+    /// File job-spec writes the Chainlink job spec to, printed when omitted
+    #[arg(long)]
+    job_spec: Option<PathBuf>,
 
     #[arg(long, default_value = "world")]
     message: String,
@@ -92,22 +277,86 @@ struct Args {
 #[tokio::main]
 pub async fn main() {
     dotenv().ok();
+    telemetry::init_tracing();
 
     if let Err(e) = execute(Args::parse()).await {
         println!("error: {:?}", e);
-        return;
     }
 }
 
 async fn execute(args: Args) -> CliResult<()> {
+    if args.multi_sig && !proposes(&args.command, &args.contract) {
+        let command = args
+            .command
+            .to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default();
+        return Err(Error::Internal(format!(
+            "{} cannot go through the multi-sig wallet",
+            command
+        )));
+    }
     let network = Network::from_str(&args.network).unwrap();
     let to_address = impl_ethers_rs::to_address(args.to_address.clone());
+    let token_id = impl_ethers_rs::to_token_id(&args.token_id)?;
+    // Parsed only by the commands that take them, so a bad value does not break the others.
+    let wei = || unit::to_wei(&args.ether, unit::Unit::Ether);
+    let token_ids = || {
+        args.token_ids
+            .iter()
+            .map(|token_id| impl_ethers_rs::to_token_id(token_id))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let amounts = || {
+        args.amounts
+            .iter()
+            .map(|amount| unit::parse_units(amount, 0))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let address = args.address.clone().map(impl_ethers_rs::to_address);
+    let clients = Clients {
+        network,
+        address,
+        market_address: match args.contract {
+            Contract::NftMarket => address,
+            _ => args.market_address.clone().map(impl_ethers_rs::to_address),
+        },
+        multi_sig_address: match args.contract {
+            Contract::MultiSigWallet => address,
+            _ => args
+                .multi_sig_address
+                .clone()
+                .map(impl_ethers_rs::to_address),
+        },
+    };
+    let owner_address = || {
+        impl_ethers_rs::to_address(
+            args.owner_address
+                .clone()
+                .unwrap_or_else(|| env::var("WALLET_ADDRESS").expect("WALLET_ADDRESS must be set")),
+        )
+    };
+
+    let nft_address = || match (args.nft_address.clone(), &args.contract) {
+        (Some(nft_address), _) => Ok(impl_ethers_rs::to_address(nft_address)),
+        (None, Contract::NftMarket | Contract::MultiSigWallet) => {
+            Err(Error::Internal("--nft-address is required".to_string()))
+        }
+        (None, contract) => Ok(clients.address(contract)),
+    };
+
+    let operator_address = || {
+        args.operator_address
+            .clone()
+            .map(impl_ethers_rs::to_address)
+            .unwrap_or_else(|| clients.market_address())
+    };
 
     match args.command {
         Command::Balance => impl_ethers_rs::get_balance(network)
             .await
             .map_err(Error::from),
-        Command::SendEth => impl_ethers_rs::send_eth(network, args.ether, args.to_address)
+        Command::SendEth => impl_ethers_rs::send_eth(network, wei()?, args.to_address)
             .await
             .map_err(Error::from),
         Command::CreateMetadata => {
@@ -123,31 +372,51 @@ async fn execute(args: Args) -> CliResult<()> {
         }
         Command::Mint => match args.contract {
             Contract::Nft721 => {
-                let cli = impl_ethers_rs::nft_721::client::Client::new(network);
+                let cli = clients.nft_721();
+                if args.multi_sig {
+                    return clients
+                        .propose(cli.proposal("mint", args.content_hash.clone())?)
+                        .await;
+                }
                 cli.mint(args.content_hash.clone())
                     .await
                     .map_err(Error::from)
             }
             Contract::Nft1155 => {
-                let cli = impl_ethers_rs::nft_1155::client::Client::new(network);
+                let cli = clients.nft_1155();
+                if args.multi_sig {
+                    return clients
+                        .propose(cli.proposal("mint", (args.content_hash.clone(), args.amount))?)
+                        .await;
+                }
                 cli.mint(args.content_hash.clone(), args.amount)
                     .await
                     .map_err(Error::from)
             }
             Contract::Sbt721 => {
-                let cli = impl_ethers_rs::sbt_721::client::Client::new(network);
+                let cli = clients.sbt_721();
+                if args.multi_sig {
+                    return clients
+                        .propose(cli.proposal("mint", args.content_hash.clone())?)
+                        .await;
+                }
                 cli.mint(args.content_hash.clone())
                     .await
                     .map_err(Error::from)
             }
             Contract::RevealNft721 => {
-                let cli = impl_ethers_rs::reveal_nft_721::client::Client::new(network);
+                let cli = clients.reveal_nft_721();
+                if args.multi_sig {
+                    return clients
+                        .propose(cli.proposal("mint", args.content_hash.clone())?)
+                        .await;
+                }
                 cli.mint(args.content_hash.clone())
                     .await
                     .map_err(Error::from)
             }
             Contract::MetaTransactionWallet => {
-                let cli = impl_ethers_rs::meta_transaction_wallet::client::Client::new(network);
+                let cli = clients.meta_transaction_wallet();
                 cli.mint(to_address, args.content_hash.clone())
                     .await
                     .map_err(Error::from)
@@ -155,32 +424,53 @@ async fn execute(args: Args) -> CliResult<()> {
             _ => Err(Error::Internal("invalid params".to_string())),
         },
         Command::Transfer => match args.contract {
-            Contract::Nft721 => {
-                let cli = impl_ethers_rs::nft_721::client::Client::new(network);
-                cli.transfer(to_address, args.token_id)
-                    .await
-                    .map_err(Error::from)
-            }
             Contract::Nft1155 => {
-                let cli = impl_ethers_rs::nft_1155::client::Client::new(network);
-                cli.transfer(to_address, args.token_id)
-                    .await
-                    .map_err(Error::from)
+                let cli = clients.nft_1155();
+                let data = impl_ethers_rs::to_bytes(&args.data)?;
+                let token_ids = token_ids()?;
+                let amounts = amounts()?;
+                if token_ids.is_empty() {
+                    let amount = amounts.first().copied().unwrap_or_else(|| 1.into());
+                    cli.transfer(to_address, token_id, amount, data)
+                        .await
+                        .map_err(Error::from)
+                } else {
+                    let amounts = if amounts.is_empty() {
+                        vec![1.into(); token_ids.len()]
+                    } else {
+                        amounts
+                    };
+                    cli.batch_transfer(to_address, token_ids, amounts, data)
+                        .await
+                        .map_err(Error::from)
+                }
             }
-            Contract::RevealNft721 => {
-                let cli = impl_ethers_rs::reveal_nft_721::client::Client::new(network);
-                cli.transfer(to_address, args.token_id)
-                    .await
-                    .map_err(Error::from)
+            Contract::Nft721 | Contract::RevealNft721 => {
+                let cli = clients.transferable_erc721(&args.contract)?;
+                let from_address = match args.from_address.clone() {
+                    Some(from_address) => impl_ethers_rs::to_address(from_address),
+                    None => cli.wallet_address(),
+                };
+                if args.safe {
+                    let data = impl_ethers_rs::to_bytes(&args.data)?;
+                    cli.safe_transfer_from(from_address, to_address, token_id, data)
+                        .await
+                        .map_err(Error::from)
+                } else {
+                    cli.transfer_from(from_address, to_address, token_id)
+                        .await
+                        .map_err(Error::from)
+                }
             }
             _ => Err(Error::Internal("invalid params".to_string())),
         },
         Command::Info => match args.contract {
             Contract::Nft721 => {
-                let cli = impl_ethers_rs::nft_721::client::Client::new(network);
+                let cli = clients.nft_721();
                 println!("------------------------------------------------------------");
-                println!("Nft721 info: {}", network.nft_721_address());
+                println!("Nft721 info: {:?}", clients.address(&args.contract));
                 println!("name = {}", cli.name().await?);
+                println!("symbol = {}", cli.symbol().await?);
                 println!("latestTokenId = {}", cli.latest_token_id().await?);
                 println!("totalSupply = {:?}", cli.total_supply().await?);
                 println!("totalOwned = {:?}", cli.total_owned().await?);
@@ -188,9 +478,9 @@ async fn execute(args: Args) -> CliResult<()> {
                 Ok(())
             }
             Contract::Nft1155 => {
-                let cli = impl_ethers_rs::nft_1155::client::Client::new(network);
+                let cli = clients.nft_1155();
                 println!("------------------------------------------------------------");
-                println!("Nft1155 info: {}", network.nft_1155_address());
+                println!("Nft1155 info: {:?}", clients.address(&args.contract));
                 println!("name = {}", cli.name().await?);
                 println!("latestTokenId = {}", cli.latest_token_id().await?);
                 println!("totalSupply = {:?}", cli.total_supply().await?);
@@ -199,38 +489,603 @@ async fn execute(args: Args) -> CliResult<()> {
                 Ok(())
             }
             Contract::Sbt721 => {
-                let cli = impl_ethers_rs::sbt_721::client::Client::new(network);
+                let cli = clients.sbt_721();
                 println!("------------------------------------------------------------");
-                println!("Sbt721 info: {}", network.sbt_721_address());
+                println!("Sbt721 info: {:?}", clients.address(&args.contract));
                 println!("name = {}", cli.name().await?);
+                println!("symbol = {}", cli.symbol().await?);
                 println!("totalSupply = {:?}", cli.total_supply().await?);
                 println!("------------------------------------------------------------");
                 Ok(())
             }
             Contract::RevealNft721 => {
-                let cli = impl_ethers_rs::reveal_nft_721::client::Client::new(network);
+                let cli = clients.reveal_nft_721();
                 println!("------------------------------------------------------------");
-                println!("RevealNft721 info: {}", network.reveal_nft_address());
+                println!("RevealNft721 info: {:?}", clients.address(&args.contract));
                 println!("name = {}", cli.name().await?);
+                println!("symbol = {}", cli.symbol().await?);
                 println!("totalSupply = {:?}", cli.total_supply().await?);
                 println!("getCurrentHour = {}", cli.get_current_hour().await?);
                 println!("------------------------------------------------------------");
                 Ok(())
             }
             Contract::NftMarket => {
-                let market = impl_ethers_rs::nft_market::client::Client::new(Network::Polygon);
+                let market = clients.nft_market();
                 let keys = market.get_sell_order_keys().await.map_err(Error::from)?;
                 for key in keys {
                     println!("key: {:?}", key);
                 }
-                let items = market.get_all_sell_order().await.map_err(Error::from)?;
-                for item in items {
-                    println!("{:?}", item);
+                let listings = market
+                    .get_all_sell_order_with_royalty()
+                    .await
+                    .map_err(Error::from)?;
+                for listing in listings {
+                    println!("{:?}", listing.nft);
+                    if let Some(royalty) = listing.royalty {
+                        println!(
+                            "  royalty: {} ether to {:?}",
+                            unit::from_wei(royalty.amount, unit::Unit::Ether),
+                            royalty.receiver
+                        );
+                    }
                 }
                 Ok(())
             }
             _ => Err(Error::Internal("invalid params".to_string())),
         },
+        Command::OwnerOf => {
+            let cli = clients.erc721(&args.contract)?;
+            println!(
+                "ownerOf({}) = {:?}",
+                token_id,
+                cli.owner_of(token_id).await?
+            );
+            Ok(())
+        }
+        Command::BalanceOf => match args.contract {
+            Contract::Nft1155 => {
+                let cli = clients.nft_1155();
+                let owner_address = owner_address();
+                let token_ids = token_ids()?;
+                if token_ids.is_empty() {
+                    println!(
+                        "balanceOf({:?}, {}) = {}",
+                        owner_address,
+                        token_id,
+                        cli.balance_of(owner_address, token_id).await?
+                    );
+                } else {
+                    let balances = cli
+                        .balance_of_batch(vec![owner_address; token_ids.len()], token_ids.clone())
+                        .await?;
+                    for (token_id, balance) in token_ids.iter().zip(balances) {
+                        println!("balanceOf({:?}, {}) = {}", owner_address, token_id, balance);
+                    }
+                }
+                Ok(())
+            }
+            _ => {
+                let cli = clients.erc721(&args.contract)?;
+                let owner_address = owner_address();
+                println!(
+                    "balanceOf({:?}) = {}",
+                    owner_address,
+                    cli.balance_of(owner_address).await?
+                );
+                Ok(())
+            }
+        },
+        Command::TokenUri => match args.contract {
+            Contract::Nft1155 => {
+                let cli = clients.nft_1155();
+                println!("uri({}) = {}", token_id, cli.uri(token_id).await?);
+                Ok(())
+            }
+            _ => {
+                let cli = clients.erc721(&args.contract)?;
+                println!(
+                    "tokenURI({}) = {}",
+                    token_id,
+                    cli.token_uri(token_id).await?
+                );
+                Ok(())
+            }
+        },
+        Command::TokensOfOwner => match args.contract {
+            Contract::Nft1155 => {
+                let cli = clients.nft_1155();
+                let owner_address = owner_address();
+                let holdings = cli.holdings(owner_address).await?;
+                println!("tokens of {:?}: {}", owner_address, holdings.len());
+                for (token_id, amount) in holdings {
+                    println!("{} x {}", token_id, amount);
+                }
+                Ok(())
+            }
+            _ => {
+                let cli = clients.erc721(&args.contract)?;
+                let owner_address = owner_address();
+                let token_ids = cli.tokens_of_owner(owner_address).await?;
+                println!("tokens of {:?}: {}", owner_address, token_ids.len());
+                for token_id in token_ids {
+                    println!("{}", token_id);
+                }
+                Ok(())
+            }
+        },
+        Command::IsOwner => {
+            let owner_address = owner_address();
+            let is_owner = match args.contract {
+                Contract::Nft1155 => {
+                    let cli = clients.nft_1155();
+                    cli.is_owner(token_id, owner_address).await?
+                }
+                _ => {
+                    let cli = clients.erc721(&args.contract)?;
+                    cli.owner_of(token_id).await? == owner_address
+                }
+            };
+            println!("isOwner({}, {:?}) = {}", token_id, owner_address, is_owner);
+            Ok(())
+        }
+        Command::BatchMint => {
+            let manifest = args
+                .manifest
+                .ok_or_else(|| Error::Internal("--manifest is required".to_string()))?;
+            let progress = args
+                .progress
+                .unwrap_or_else(|| manifest.with_extension("progress.json"));
+            let rows = batch_mint::read_manifest(&manifest)?;
+            let results = match args.contract {
+                Contract::Nft721 => {
+                    let cli = clients.nft_721();
+                    cli.batch_mint(rows, &progress, args.concurrency).await?
+                }
+                Contract::Nft1155 => {
+                    let cli = clients.nft_1155();
+                    cli.batch_mint(rows, &progress, args.concurrency).await?
+                }
+                Contract::Sbt721 => {
+                    let cli = clients.sbt_721();
+                    cli.batch_mint(rows, &progress, args.concurrency).await?
+                }
+                Contract::RevealNft721 => {
+                    let cli = clients.reveal_nft_721();
+                    cli.batch_mint(rows, &progress, args.concurrency).await?
+                }
+                _ => return Err(Error::Internal("invalid params".to_string())),
+            };
+            print_mint_report(&results);
+            Ok(())
+        }
+        Command::Inspect => {
+            let address = clients.address(&args.contract);
+            let res = impl_ethers_rs::erc165::inspect(network, address).await?;
+            println!("------------------------------------------------------------");
+            println!("inspect: {:?}", res.address);
+            println!("contract = {}", res.is_contract);
+            println!("owner = {:?}", res.owner);
+            println!("name = {:?}", res.name);
+            println!("symbol = {:?}", res.symbol);
+            println!("ERC165 = {}", res.erc165);
+            println!("ERC721 = {}", res.erc721);
+            println!("ERC721Metadata = {}", res.erc721_metadata);
+            println!("ERC721Enumerable = {}", res.erc721_enumerable);
+            println!("ERC1155 = {}", res.erc1155);
+            println!("ERC1155MetadataURI = {}", res.erc1155_metadata_uri);
+            println!("ERC2981 = {}", res.erc2981);
+            println!("ERC4906 = {}", res.erc4906);
+            match res.schema() {
+                Some(Schema::ERC721) => {
+                    let cli = impl_ethers_rs::nft_721::client::Client::with_address(
+                        network,
+                        address,
+                        Account::from_env(),
+                    );
+                    if res.erc721_enumerable {
+                        println!("totalSupply = {}", cli.total_supply().await?);
+                    }
+                    if res.erc721_metadata {
+                        println!(
+                            "tokenURI({}) = {}",
+                            token_id,
+                            cli.token_uri(token_id).await?
+                        );
+                    }
+                }
+                Some(Schema::ERC1155) => {
+                    let cli = impl_ethers_rs::nft_1155::client::Client::with_address(
+                        network,
+                        address,
+                        Account::from_env(),
+                    );
+                    if res.erc1155_metadata_uri {
+                        println!("uri({}) = {}", token_id, cli.uri(token_id).await?);
+                    }
+                }
+                None => {}
+            }
+            println!("------------------------------------------------------------");
+            Ok(())
+        }
+        Command::RoyaltyInfo => {
+            let address = clients.address(&args.contract);
+            match impl_ethers_rs::erc2981::royalty_info(network, address, token_id, wei()?).await? {
+                Some(royalty) => println!(
+                    "royaltyInfo({}, {} ether) = {} ether to {:?}",
+                    token_id,
+                    args.ether,
+                    unit::from_wei(royalty.amount, unit::Unit::Ether),
+                    royalty.receiver
+                ),
+                None => println!("{:?} does not support ERC-2981", address),
+            }
+            Ok(())
+        }
+        Command::TokenMetadata => {
+            let uri = match args.contract {
+                Contract::Nft1155 => clients.nft_1155().uri(token_id).await?,
+                _ => clients.erc721(&args.contract)?.token_uri(token_id).await?,
+            };
+            let resolver = ipfs::resolver::Resolver::new()?;
+            let metadata = resolver.resolve(&uri).await?;
+            println!("------------------------------------------------------------");
+            println!("tokenURI = {}", uri);
+            println!("name = {}", metadata.name);
+            println!("description = {}", metadata.description);
+            println!("image = {}", metadata.image);
+            println!("imageUrl = {}", resolver.http_url(&metadata.image));
+            println!("------------------------------------------------------------");
+            Ok(())
+        }
+        Command::Snapshot => {
+            let address = clients.address(&args.contract);
+            let snapshot = match args.contract {
+                Contract::Nft1155 => {
+                    impl_ethers_rs::snapshot::erc1155(
+                        network,
+                        address,
+                        args.block,
+                        args.from_block,
+                        args.concurrency,
+                    )
+                    .await?
+                }
+                Contract::Nft721 | Contract::Sbt721 | Contract::RevealNft721 => {
+                    impl_ethers_rs::snapshot::erc721(network, address, args.block, args.concurrency)
+                        .await?
+                }
+                _ => return Err(Error::Internal("invalid params".to_string())),
+            };
+            match args.output.extension().and_then(|ext| ext.to_str()) {
+                Some("jsonl") => snapshot.write_jsonl(&args.output)?,
+                _ => snapshot.write_csv(&args.output)?,
+            }
+            println!(
+                "{} rows at block {} written to {}",
+                snapshot.rows.len(),
+                snapshot.block,
+                args.output.display()
+            );
+            Ok(())
+        }
+        Command::Airdrop => {
+            let recipients = args
+                .recipients
+                .ok_or_else(|| Error::Internal("--recipients is required".to_string()))?;
+            let progress = args
+                .progress
+                .unwrap_or_else(|| recipients.with_extension("progress.json"));
+            let rows = airdrop::read_recipients(&recipients)?;
+            let results = match args.contract {
+                Contract::Nft1155 => {
+                    clients
+                        .nft_1155()
+                        .airdrop(rows, &progress, args.rate, args.concurrency)
+                        .await?
+                }
+                Contract::Nft721 => {
+                    clients
+                        .nft_721()
+                        .airdrop(rows, &progress, args.rate, args.concurrency)
+                        .await?
+                }
+                Contract::RevealNft721 => {
+                    clients
+                        .reveal_nft_721()
+                        .airdrop(rows, &progress, args.rate, args.concurrency)
+                        .await?
+                }
+                _ => return Err(Error::Internal("invalid params".to_string())),
+            };
+            print_airdrop_report(&results);
+            Ok(())
+        }
+        Command::ChainlinkConfig => {
+            let config = clients.reveal_nft_721().chainlink_config().await?;
+            let decimals = clients.token_decimals(config.token).await?;
+            println!("------------------------------------------------------------");
+            println!("token = {:?}", config.token);
+            println!("oracle = {:?}", config.oracle);
+            println!("jobId = {}", config.job_id);
+            println!("fee = {} LINK", unit::format_units(config.fee, decimals));
+            println!("------------------------------------------------------------");
+            Ok(())
+        }
+        Command::SetChainlinkToken => {
+            let link_address =
+                impl_ethers_rs::to_address(required(args.link_address, "--link-address")?);
+            let cli = clients.reveal_nft_721();
+            if args.multi_sig {
+                return clients
+                    .propose(cli.proposal("setChainlinkTokenString", link_address)?)
+                    .await;
+            }
+            cli.set_chainlink_token(link_address)
+                .await
+                .map_err(Error::from)
+        }
+        Command::SetChainlinkFee => {
+            let cli = clients.reveal_nft_721();
+            let token = cli.chainlink_config().await?.token;
+            let decimals = clients.token_decimals(token).await?;
+            let fee = unit::parse_units(&required(args.fee, "--fee")?, decimals)?;
+            if args.multi_sig {
+                return clients.propose(cli.proposal("setChainlinkFee", fee)?).await;
+            }
+            cli.set_chainlink_fee(fee).await.map_err(Error::from)
+        }
+        Command::SetOracleAddress => {
+            let oracle_address =
+                impl_ethers_rs::to_address(required(args.oracle_address, "--oracle-address")?);
+            let cli = clients.reveal_nft_721();
+            if args.multi_sig {
+                return clients
+                    .propose(cli.proposal("setOracleAddress", oracle_address)?)
+                    .await;
+            }
+            cli.set_oracle_address(oracle_address)
+                .await
+                .map_err(Error::from)
+        }
+        Command::SetTimeAdapterJobId => {
+            let job_id = required(args.job_id, "--job-id")?;
+            let cli = clients.reveal_nft_721();
+            if args.multi_sig {
+                return clients
+                    .propose(cli.proposal("setTimeAdapterJobId", job_id.replace('-', ""))?)
+                    .await;
+            }
+            cli.set_time_adapter_job_id(job_id)
+                .await
+                .map_err(Error::from)
+        }
+        Command::CancelRequest => {
+            let request_id = required(args.request_id, "--request-id")?
+                .parse()
+                .map_err(|_| Error::Internal("invalid request id".to_string()))?;
+            let selector = impl_ethers_rs::reveal_nft_721::client::fulfill_update_time_selector();
+            let expiration = required(args.expiration, "--expiration")?.into();
+            let cli = clients.reveal_nft_721();
+            if args.multi_sig {
+                return clients
+                    .propose(cli.proposal("cancelRequest", (request_id, selector, expiration))?)
+                    .await;
+            }
+            cli.cancel_request(request_id, selector, expiration)
+                .await
+                .map_err(Error::from)
+        }
+        Command::WithdrawLink => {
+            let cli = clients.reveal_nft_721();
+            if args.multi_sig {
+                return clients.propose(cli.proposal("withdrawLink", ())?).await;
+            }
+            cli.withdraw_link().await.map_err(Error::from)
+        }
+        Command::SetTimestampForDebug => {
+            let timestamp = required(args.timestamp, "--timestamp")?.into();
+            let cli = clients.reveal_nft_721();
+            if args.multi_sig {
+                return clients
+                    .propose(cli.proposal("setTimestampForDebug", timestamp)?)
+                    .await;
+            }
+            cli.set_timestamp_for_debug(timestamp)
+                .await
+                .map_err(Error::from)
+        }
+        Command::RevealStatus => {
+            let cli = clients.reveal_nft_721();
+            let token_ids = token_ids()?;
+            let token_ids = if token_ids.is_empty() {
+                cli.all_tokens().await?
+            } else {
+                token_ids
+            };
+            let status = cli.reveal_status(token_ids).await?;
+            println!("------------------------------------------------------------");
+            match (status.oracle_timestamp, status.oracle_age) {
+                (Some(timestamp), Some(age)) => println!(
+                    "oracle = {} ({}, {} minutes ago)",
+                    timestamp,
+                    status.oracle_now,
+                    age.num_minutes()
+                ),
+                _ => println!("oracle = never answered"),
+            }
+            println!("revealed = {}", status.revealed);
+            println!("nextFlip = {}", status.next_flip);
+            for token in status.tokens {
+                println!(
+                    "{}\t{}\t{}",
+                    token.token_id,
+                    if token.revealed { "revealed" } else { "hidden" },
+                    token.token_uri
+                );
+            }
+            println!("------------------------------------------------------------");
+            Ok(())
+        }
+        Command::LinkStatus => {
+            let reveal = clients.reveal_nft_721();
+            let config = impl_ethers_rs::link_monitor::Config::from_env();
+            let mut status =
+                impl_ethers_rs::link_monitor::check(&reveal, Account::from_env(), &config).await?;
+            if args.top_up {
+                if config.top_up.is_none() {
+                    return Err(Error::Internal(
+                        "--top-up needs LINK_TOP_UP_AMOUNT".to_string(),
+                    ));
+                }
+                impl_ethers_rs::link_monitor::top_up(
+                    &reveal,
+                    Account::from_env(),
+                    &config,
+                    &mut status,
+                )
+                .await?;
+            }
+            println!("------------------------------------------------------------");
+            println!("token = {:?}", status.token);
+            println!("balance = {} LINK", status.format(status.balance));
+            println!("minBalance = {} LINK", status.format(status.min_balance));
+            println!("requestsLeft = {}", status.requests_left());
+            if let Some(amount) = status.topped_up {
+                println!("toppedUp = {} LINK", status.format(amount));
+            } else if status.is_low() {
+                println!("low = true");
+            }
+            println!("------------------------------------------------------------");
+            Ok(())
+        }
+        Command::JobSpec => {
+            let spec = impl_ethers_rs::job_spec::time_adapter(network, args.job_id)?;
+            match args.job_spec {
+                Some(path) => {
+                    std::fs::write(&path, spec)
+                        .map_err(|e| Error::Internal(format!("write error: {:?}", e)))?;
+                    println!("wrote {}", path.display());
+                }
+                None => print!("{}", spec),
+            }
+            Ok(())
+        }
+        Command::MultiSigInfo => {
+            let cli = clients.multi_sig_wallet();
+            let owners = cli.get_owners().await?;
+            let required = cli.get_required().await?;
+            let transaction_count = cli.transaction_count().await?;
+            println!("------------------------------------------------------------");
+            println!("address = {:?}", cli.address);
+            for owner in owners {
+                println!("owner = {:?}", owner);
+            }
+            println!("required = {}", required);
+            println!("transactionCount = {}", transaction_count);
+            println!("------------------------------------------------------------");
+            Ok(())
+        }
+        Command::MultiSigTransaction => {
+            let transaction_id = required(args.transaction_id, "--transaction-id")?.into();
+            let cli = clients.multi_sig_wallet();
+            let transaction = cli.get_transaction(transaction_id).await?;
+            let confirmations = cli.confirmations(transaction_id).await?;
+            println!("------------------------------------------------------------");
+            println!("id = {}", transaction.id);
+            println!("destination = {:?}", transaction.destination);
+            println!(
+                "value = {} ether",
+                unit::from_wei(transaction.value, unit::Unit::Ether)
+            );
+            println!("data = {}", transaction.data);
+            if let Some(call) = decode_call(&transaction.data) {
+                println!("call = {}", call);
+            }
+            println!("executed = {}", transaction.executed);
+            for owner in confirmations {
+                println!("confirmedBy = {:?}", owner);
+            }
+            println!("------------------------------------------------------------");
+            Ok(())
+        }
+        Command::MultiSigPending => {
+            let cli = clients.multi_sig_wallet();
+            let required = cli.get_required().await?;
+            let pending = cli.pending_transactions().await?;
+            for pending in pending.iter() {
+                let transaction = &pending.transaction;
+                println!("------------------------------------------------------------");
+                println!("id = {}", transaction.id);
+                println!("destination = {:?}", transaction.destination);
+                println!(
+                    "value = {} ether",
+                    unit::from_wei(transaction.value, unit::Unit::Ether)
+                );
+                match &pending.call {
+                    Some(call) => println!("call = {}", call),
+                    None => println!("data = {}", transaction.data),
+                }
+                println!(
+                    "confirmations = {} / {}",
+                    pending.confirmations.len(),
+                    required
+                );
+                for owner in pending.confirmations.iter() {
+                    println!("confirmedBy = {:?}", owner);
+                }
+            }
+            println!("------------------------------------------------------------");
+            println!("{} pending transactions", pending.len());
+            Ok(())
+        }
+        Command::MultiSigAddOwner => {
+            let owner = required(args.owner_address.clone(), "--owner-address")?;
+            let events = clients
+                .multi_sig_wallet()
+                .add_owner(impl_ethers_rs::to_address(owner))
+                .await?;
+            print_multi_sig_events(&events);
+            Ok(())
+        }
+        Command::MultiSigRemoveOwner => {
+            let owner = required(args.owner_address.clone(), "--owner-address")?;
+            let events = clients
+                .multi_sig_wallet()
+                .remove_owner(impl_ethers_rs::to_address(owner))
+                .await?;
+            print_multi_sig_events(&events);
+            Ok(())
+        }
+        Command::MultiSigChangeRequirement => {
+            let confirmations = required(args.required, "--required")?;
+            let events = clients
+                .multi_sig_wallet()
+                .change_requirement(confirmations.into())
+                .await?;
+            print_multi_sig_events(&events);
+            Ok(())
+        }
+        Command::MultiSigSubmit => {
+            let destination = required(args.destination.clone(), "--destination")?;
+            let value = unit::to_wei(&args.value, unit::Unit::Ether)?;
+            let data = impl_ethers_rs::to_bytes(&args.data)?;
+            let events = clients
+                .multi_sig_wallet()
+                .submit_transaction(impl_ethers_rs::to_address(destination), value, data)
+                .await?;
+            print_multi_sig_events(&events);
+            Ok(())
+        }
+        Command::MultiSigConfirm => {
+            let transaction_id = required(args.transaction_id, "--transaction-id")?.into();
+            let events = clients
+                .multi_sig_wallet()
+                .confirm_transaction(transaction_id)
+                .await?;
+            print_multi_sig_events(&events);
+            Ok(())
+        }
         Command::KeyGen => impl_ethers_rs::generate_keys().await.map_err(Error::from),
         Command::Sign => impl_ethers_rs::sign(args.message)
             .await
@@ -240,81 +1095,416 @@ async fn execute(args: Args) -> CliResult<()> {
             .map_err(Error::from),
         Command::Deploy => match args.contract {
             Contract::Nft721 => {
-                let cli = impl_ethers_rs::nft_721::client::Client::new(network);
+                let cli = clients.nft_721();
                 cli.deploy().await.map_err(Error::from)
             }
             Contract::Nft1155 => {
-                let cli = impl_ethers_rs::nft_1155::client::Client::new(network);
+                let cli = clients.nft_1155();
                 cli.deploy().await.map_err(Error::from)
             }
             Contract::Sbt721 => {
-                let cli = impl_ethers_rs::sbt_721::client::Client::new(network);
+                let cli = clients.sbt_721();
                 cli.deploy().await.map_err(Error::from)
             }
             Contract::RevealNft721 => {
-                let cli = impl_ethers_rs::reveal_nft_721::client::Client::new(network);
+                let cli = clients.reveal_nft_721();
                 cli.deploy().await.map_err(Error::from)
             }
             _ => Err(Error::Internal("invalid params".to_string())),
         },
         Command::UpdateTime => {
             if network == Network::Ethereum {
-                let cli = impl_ethers_rs::reveal_nft_721::client::Client::new(network);
-                cli.update_time().await?;
+                let cli = clients.reveal_nft_721();
+                if args.multi_sig {
+                    return clients.propose(cli.proposal("updateTime", ())?).await;
+                }
+                let request = cli.update_time().await?;
+                println!("requestId = {:?}", request.id);
+                if let Some(expiration) = request.expiration {
+                    println!("expiration = {}", expiration);
+                }
             }
 
             Ok(())
         }
         Command::NftMarketSell => {
-            let market = impl_ethers_rs::nft_market::client::Client::new(Network::Polygon);
+            let market = clients.nft_market();
             market
                 .sell_order(
                     env::var("SELLER_SECRET").expect("SELLER_SECRET must be set"),
-                    Network::Polygon.nft_721_address(),
-                    args.token_id,
-                    args.ether,
+                    format!("{:?}", nft_address()?),
+                    token_id,
+                    wei()?,
                 )
                 .await
                 .map_err(Error::from)
         }
         Command::NftMarketCancel => {
-            let market = impl_ethers_rs::nft_market::client::Client::new(Network::Polygon);
+            let market = clients.nft_market();
             market
                 .cancel_order(
                     env::var("SELLER_SECRET").expect("SELLER_SECRET must be set"),
-                    Network::Polygon.nft_721_address(),
-                    args.token_id,
+                    format!("{:?}", nft_address()?),
+                    token_id,
                 )
                 .await
                 .map_err(Error::from)
         }
+        Command::NftMarketCancelByAdmin => {
+            let contract_address = nft_address()?;
+            if args.multi_sig {
+                // The wallet owns the market then, so there is no NFT_MARKET_OWNER_SECRET.
+                let market = impl_ethers_rs::nft_market::client::Client::with_address(
+                    network,
+                    clients.market_address(),
+                    Account::from_env(),
+                );
+                return clients
+                    .propose(market.proposal("cancelOrderByAdmin", (contract_address, token_id))?)
+                    .await;
+            }
+            clients
+                .nft_market()
+                .cancel_order_by_admin(format!("{:?}", contract_address), token_id)
+                .await
+                .map_err(Error::from)
+        }
         Command::NftMarketBuy => {
-            let market = impl_ethers_rs::nft_market::client::Client::new(Network::Polygon);
+            let market = clients.nft_market();
             market
                 .buy_order(
                     env::var("BUYER_SECRET").expect("BUYER_SECRET must be set"),
-                    Network::Polygon.nft_721_address(),
-                    args.token_id,
-                    args.ether,
+                    format!("{:?}", nft_address()?),
+                    token_id,
+                    wei()?,
                 )
                 .await
                 .map_err(Error::from)
         }
         Command::ApproveForSell => match args.contract {
-            Contract::Nft721 => {
-                let cli = impl_ethers_rs::nft_721::client::Client::new(network);
-                cli.set_approval_for_all().await.map_err(Error::from)
+            Contract::Nft1155 => {
+                let cli = clients.nft_1155();
+                cli.set_approval_for_all(operator_address(), true)
+                    .await
+                    .map_err(Error::from)
+            }
+            Contract::Nft721 | Contract::RevealNft721 => {
+                let cli = clients.transferable_erc721(&args.contract)?;
+                cli.set_approval_for_all(operator_address(), true)
+                    .await
+                    .map_err(Error::from)
             }
+            _ => Err(Error::Internal("invalid params".to_string())),
+        },
+        Command::Approve => {
+            let cli = clients.transferable_erc721(&args.contract)?;
+            cli.approve(to_address, token_id).await.map_err(Error::from)
+        }
+        Command::GetApproved => {
+            let cli = clients.transferable_erc721(&args.contract)?;
+            let approved = cli.get_approved(token_id).await?;
+            println!("getApproved({}) = {:?}", token_id, approved);
+            Ok(())
+        }
+        Command::SetApprovalForAll => match args.contract {
             Contract::Nft1155 => {
-                let cli = impl_ethers_rs::nft_1155::client::Client::new(network);
-                cli.set_approval_for_all().await.map_err(Error::from)
+                let cli = clients.nft_1155();
+                cli.set_approval_for_all(operator_address(), args.approved)
+                    .await
+                    .map_err(Error::from)
             }
-            Contract::RevealNft721 => {
-                let cli = impl_ethers_rs::reveal_nft_721::client::Client::new(network);
-                cli.set_approval_for_all().await.map_err(Error::from)
+            _ => {
+                let cli = clients.transferable_erc721(&args.contract)?;
+                cli.set_approval_for_all(operator_address(), args.approved)
+                    .await
+                    .map_err(Error::from)
             }
-            _ => return Err(Error::Internal("invalid params".to_string())),
         },
+        Command::IsApprovedForAll => {
+            let owner_address = owner_address();
+            let operator_address = operator_address();
+            let approved = match args.contract {
+                Contract::Nft1155 => {
+                    let cli = clients.nft_1155();
+                    cli.is_approved_for_all(owner_address, operator_address)
+                        .await?
+                }
+                _ => {
+                    let cli = clients.transferable_erc721(&args.contract)?;
+                    cli.is_approved_for_all(owner_address, operator_address)
+                        .await?
+                }
+            };
+            println!(
+                "isApprovedForAll({:?}, {:?}) = {}",
+                owner_address, operator_address, approved
+            );
+            Ok(())
+        }
+        Command::ListApprovals => {
+            let owner_address = owner_address();
+            let operators = match args.contract {
+                Contract::Nft1155 => {
+                    let cli = clients.nft_1155();
+                    cli.approved_operators(owner_address, args.from_block)
+                        .await?
+                }
+                _ => {
+                    let cli = clients.transferable_erc721(&args.contract)?;
+                    cli.approved_operators(owner_address, args.from_block)
+                        .await?
+                }
+            };
+            for operator in operators.iter() {
+                println!("{:?}", operator);
+            }
+            println!("{} approved operators", operators.len());
+            Ok(())
+        }
+        Command::RevokeApprovals => {
+            let accounts = Account::all_from_env()?;
+            let revoked = match args.contract {
+                Contract::Nft1155 => {
+                    let cli = clients.nft_1155();
+                    cli.revoke_approvals(&accounts, args.from_block).await?
+                }
+                _ => {
+                    let cli = clients.transferable_erc721(&args.contract)?;
+                    cli.revoke_approvals(&accounts, args.from_block).await?
+                }
+            };
+            for (owner, operator) in revoked.iter() {
+                println!("{:?} revoked {:?}", owner, operator);
+            }
+            println!("{} approvals revoked", revoked.len());
+            Ok(())
+        }
+    }
+}
+
+/// Whether `command` on `contract` can be submitted to the MultiSigWallet with `--multi-sig`;
+/// every other command is rejected rather than sent from our wallet.
+fn proposes(command: &Command, contract: &Contract) -> bool {
+    match command {
+        Command::Mint => !matches!(contract, Contract::MetaTransactionWallet),
+        Command::UpdateTime
+        | Command::NftMarketCancelByAdmin
+        | Command::SetChainlinkToken
+        | Command::SetChainlinkFee
+        | Command::SetOracleAddress
+        | Command::SetTimeAdapterJobId
+        | Command::CancelRequest
+        | Command::WithdrawLink
+        | Command::SetTimestampForDebug => true,
+        _ => false,
+    }
+}
+
+/// Builds clients for `--network`, bound to `--address` / `--market-address` /
+/// `--multi-sig-address` when given instead of the contracts configured in the environment.
+struct Clients {
+    network: Network,
+    address: Option<Address>,
+    market_address: Option<Address>,
+    multi_sig_address: Option<Address>,
+}
+
+impl Clients {
+    fn address(&self, contract: &Contract) -> Address {
+        self.address.unwrap_or_else(|| {
+            impl_ethers_rs::to_address(match contract {
+                Contract::Nft721 => self.network.nft_721_address(),
+                Contract::Nft1155 => self.network.nft_1155_address(),
+                Contract::Sbt721 => self.network.sbt_721_address(),
+                Contract::RevealNft721 => self.network.reveal_nft_address(),
+                Contract::NftMarket => self.network.nft_market_address(),
+                Contract::MetaTransactionWallet => {
+                    self.network.meta_transactional_nft_721_address()
+                }
+                Contract::MultiSigWallet => self.network.multi_sig_wallet_address(),
+            })
+        })
+    }
+
+    fn market_address(&self) -> Address {
+        self.market_address
+            .unwrap_or_else(|| impl_ethers_rs::to_address(self.network.nft_market_address()))
+    }
+
+    fn multi_sig_address(&self) -> Address {
+        self.multi_sig_address
+            .unwrap_or_else(|| impl_ethers_rs::to_address(self.network.multi_sig_wallet_address()))
+    }
+
+    /// Decimals of the ERC-20 `token`, e.g. the LINK token paying the oracle fee.
+    async fn token_decimals(&self, token: Address) -> Result<u32, Error> {
+        let erc20 = impl_ethers_rs::erc20::Client::new(self.network, token, Account::from_env());
+        Ok(erc20.decimals().await?.into())
+    }
+
+    fn nft_721(&self) -> impl_ethers_rs::nft_721::client::Client {
+        impl_ethers_rs::nft_721::client::Client::with_address(
+            self.network,
+            self.address(&Contract::Nft721),
+            Account::from_env(),
+        )
+    }
+
+    fn nft_1155(&self) -> impl_ethers_rs::nft_1155::client::Client {
+        impl_ethers_rs::nft_1155::client::Client::with_address(
+            self.network,
+            self.address(&Contract::Nft1155),
+            Account::from_env(),
+        )
+    }
+
+    fn sbt_721(&self) -> impl_ethers_rs::sbt_721::client::Client {
+        impl_ethers_rs::sbt_721::client::Client::with_address(
+            self.network,
+            self.address(&Contract::Sbt721),
+            Account::from_env(),
+        )
+    }
+
+    fn reveal_nft_721(&self) -> impl_ethers_rs::reveal_nft_721::client::Client {
+        impl_ethers_rs::reveal_nft_721::client::Client::with_address(
+            self.network,
+            self.address(&Contract::RevealNft721),
+            Account::from_env(),
+        )
+    }
+
+    fn meta_transaction_wallet(&self) -> impl_ethers_rs::meta_transaction_wallet::client::Client {
+        let relayer_secret = env::var("RELAYER_SECRET").expect("RELAYER_SECRET must be set");
+
+        impl_ethers_rs::meta_transaction_wallet::client::Client::with_address(
+            self.network,
+            impl_ethers_rs::to_address(self.network.meta_transaction_wallet_address()),
+            self.address(&Contract::MetaTransactionWallet),
+            Account::from_env(),
+            Account::new(relayer_secret).expect("RELAYER_SECRET must be a valid key"),
+        )
+    }
+
+    fn multi_sig_wallet(&self) -> impl_ethers_rs::multi_sig_wallet::client::Client {
+        impl_ethers_rs::multi_sig_wallet::client::Client::with_address(
+            self.network,
+            self.multi_sig_address(),
+            Account::from_env(),
+        )
+    }
+
+    /// Submits `proposal` to the MultiSigWallet as our wallet, one of its owners; it runs
+    /// once the other owners confirm it with multi-sig-confirm.
+    async fn propose(&self, proposal: Proposal) -> CliResult<()> {
+        let events = self.multi_sig_wallet().propose(proposal).await?;
+        print_multi_sig_events(&events);
+        Ok(())
+    }
+
+    fn nft_market(&self) -> impl_ethers_rs::nft_market::client::Client {
+        let owner_secret =
+            env::var("NFT_MARKET_OWNER_SECRET").expect("NFT_MARKET_OWNER_SECRET must be set");
+
+        impl_ethers_rs::nft_market::client::Client::with_address(
+            self.network,
+            self.market_address(),
+            Account::new(owner_secret).expect("NFT_MARKET_OWNER_SECRET must be a valid key"),
+        )
+    }
+
+    fn erc721(&self, contract: &Contract) -> CliResult<Box<dyn Nft721>> {
+        match contract {
+            Contract::Nft721 => Ok(Box::new(self.nft_721())),
+            Contract::Sbt721 => Ok(Box::new(self.sbt_721())),
+            Contract::RevealNft721 => Ok(Box::new(self.reveal_nft_721())),
+            Contract::MetaTransactionWallet => Ok(Box::new(self.meta_transaction_wallet())),
+            _ => Err(Error::Internal("invalid params".to_string())),
+        }
+    }
+
+    fn transferable_erc721(&self, contract: &Contract) -> CliResult<Box<dyn TransferableNft721>> {
+        match contract {
+            Contract::Nft721 => Ok(Box::new(self.nft_721())),
+            Contract::RevealNft721 => Ok(Box::new(self.reveal_nft_721())),
+            Contract::MetaTransactionWallet => Ok(Box::new(self.meta_transaction_wallet())),
+            Contract::Sbt721 => Err(Error::Internal(
+                "sbt721 tokens cannot be transferred".to_string(),
+            )),
+            _ => Err(Error::Internal("invalid params".to_string())),
+        }
+    }
+}
+
+fn required<T>(value: Option<T>, name: &str) -> CliResult<T> {
+    value.ok_or_else(|| Error::Internal(format!("{} is required", name)))
+}
+
+fn print_mint_report(results: &[MintResult]) {
+    for result in results {
+        let status = match (&result.token_id, &result.tx_hash, &result.error) {
+            (Some(token_id), _, _) => format!("minted token {}", token_id),
+            (None, _, Some(error)) => format!("failed: {}", error),
+            (None, Some(_), None) => "pending".to_string(),
+            (None, None, None) => "not sent".to_string(),
+        };
+        let tx_hash = result
+            .tx_hash
+            .map(|tx_hash| format!("{:?}", tx_hash))
+            .unwrap_or_default();
+        println!(
+            "{}\t{}\t{}\t{}",
+            result.row, result.content_hash, tx_hash, status
+        );
+    }
+
+    let minted = results.iter().filter(|result| result.is_minted()).count();
+    println!("minted {} / {}", minted, results.len());
+}
+
+fn print_airdrop_report(results: &[AirdropResult]) {
+    for result in results {
+        let status = match (&result.error, result.confirmed) {
+            (Some(error), _) => format!("failed: {}", error),
+            (None, true) => "sent".to_string(),
+            (None, false) => "pending".to_string(),
+        };
+        let tx_hash = result
+            .tx_hash
+            .map(|tx_hash| format!("{:?}", tx_hash))
+            .unwrap_or_default();
+        println!(
+            "{}\t{:?}\t{}\t{}\t{}\t{}",
+            result.row, result.recipient, result.token_id, result.amount, tx_hash, status
+        );
+    }
+
+    let sent = results.iter().filter(|result| result.is_sent()).count();
+    println!("sent {} / {}", sent, results.len());
+}
+
+fn print_multi_sig_events(events: &[MultiSigEvent]) {
+    for event in events {
+        match event {
+            MultiSigEvent::Submission { transaction_id } => {
+                println!("submitted transaction {}", transaction_id)
+            }
+            MultiSigEvent::Confirmation {
+                sender,
+                transaction_id,
+            } => println!("{:?} confirmed transaction {}", sender, transaction_id),
+            MultiSigEvent::Execution { transaction_id } => {
+                println!("executed transaction {}", transaction_id)
+            }
+            MultiSigEvent::ExecutionFailure { transaction_id } => {
+                println!("transaction {} failed to execute", transaction_id)
+            }
+            MultiSigEvent::OwnerAdded { owner } => println!("added owner {:?}", owner),
+            MultiSigEvent::OwnerRemoved { owner } => println!("removed owner {:?}", owner),
+            MultiSigEvent::RequirementChanged { required } => {
+                println!("required confirmations changed to {}", required)
+            }
+        }
     }
 }
 
@@ -333,6 +1523,13 @@ impl From<impl_ethers_rs::Error> for Error {
     }
 }
 
+impl From<unit::Error> for Error {
+    fn from(e: unit::Error) -> Self {
+        let msg = format!("unit error: {:?}", e);
+        Self::Internal(msg)
+    }
+}
+
 impl From<ipfs::Error> for Error {
     fn from(e: ipfs::Error) -> Self {
         let msg = format!("ipfs error: {:?}", e);
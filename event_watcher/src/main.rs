@@ -1,11 +1,13 @@
 use dotenv::dotenv;
+use impl_ethers_rs::telemetry;
 
 #[tokio::main]
 pub async fn main() {
     dotenv().ok();
+    telemetry::init_tracing();
+    telemetry::init_metrics();
 
     if let Err(e) = impl_ethers_rs::event::watch_nft_transfer_event().await {
-        println!("error: {:?}", e);
-        return;
+        tracing::error!("error: {:?}", e);
     }
 }
@@ -0,0 +1,13 @@
+use dotenv::dotenv;
+use impl_ethers_rs::{fulfiller, telemetry};
+
+#[tokio::main]
+pub async fn main() {
+    dotenv().ok();
+    telemetry::init_tracing();
+    telemetry::init_metrics();
+
+    if let Err(e) = fulfiller::run(fulfiller::Config::from_env()).await {
+        tracing::error!("error: {:?}", e);
+    }
+}
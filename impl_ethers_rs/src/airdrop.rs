@@ -0,0 +1,478 @@
+use crate::batch_mint::{self, MintRow};
+use crate::progress::{self, needs_send, Progress, SentRow};
+use crate::{to_token_id, Error, EthersResult, NonceClient};
+use ethers::contract::Contract;
+use ethers::prelude::*;
+use futures::stream::{self, StreamExt};
+use prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+
+/// One recipient of an airdrop CSV (`recipient,token_id,amount,content_hash`).
+///
+/// Rows name either an already minted `token_id` held by our wallet, or a `content_hash`
+/// that is minted first. `amount` is only used by ERC-1155 collections and defaults to 1.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AirdropRow {
+    pub recipient: String,
+    #[serde(default)]
+    pub token_id: Option<String>,
+    #[serde(default)]
+    pub amount: Option<u64>,
+    #[serde(default)]
+    pub content_hash: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AirdropResult {
+    pub row: usize,
+    pub recipient: Address,
+    pub token_id: U256,
+    pub amount: U256,
+    pub tx_hash: Option<H256>,
+    #[serde(default)]
+    pub nonce: Option<U256>,
+    pub confirmed: bool,
+    /// The transaction in `tx_hash` is known not to have transferred: it reverted, or it
+    /// was dropped and its nonce reused.
+    #[serde(default)]
+    pub failed: bool,
+    pub error: Option<String>,
+}
+
+impl AirdropResult {
+    pub fn is_sent(&self) -> bool {
+        self.confirmed
+    }
+}
+
+impl SentRow for AirdropResult {
+    fn row(&self) -> usize {
+        self.row
+    }
+
+    fn tx_hash(&self) -> Option<H256> {
+        self.tx_hash
+    }
+
+    fn nonce(&self) -> Option<U256> {
+        self.nonce
+    }
+
+    fn is_done(&self) -> bool {
+        self.confirmed
+    }
+
+    fn is_failed(&self) -> bool {
+        self.failed
+    }
+
+    fn set_sent(&mut self, tx_hash: Option<H256>, nonce: Option<U256>) {
+        self.tx_hash = tx_hash;
+        self.nonce = nonce;
+    }
+
+    fn set_failed(&mut self, failed: bool) {
+        self.failed = failed;
+    }
+
+    fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+}
+
+pub fn read_recipients(path: &Path) -> EthersResult<Vec<AirdropRow>> {
+    csv::Reader::from_path(path)?
+        .deserialize()
+        .collect::<Result<Vec<AirdropRow>, _>>()
+        .map_err(Error::from)
+}
+
+/// Transfers every row of `rows` from `wallet`, sending at most `rate` transactions per
+/// second and keeping up to `concurrency` of them awaiting confirmation.
+///
+/// Everything still to send is validated before the first transaction, mints included:
+/// recipients must parse, and the wallet must hold every listed token (and enough of each
+/// 1155 id). Rows with a `content_hash` are then minted through [`batch_mint::mint`], with its
+/// own progress file.
+/// Sent transaction hashes are persisted before waiting for them, so resuming after a crash
+/// or a failed wait looks those transfers up instead of sending them again; they are only
+/// resent once their transaction reverted or was dropped for good.
+pub async fn airdrop(
+    contract: Contract<NonceClient>,
+    schema: Schema,
+    wallet: Address,
+    rows: Vec<AirdropRow>,
+    progress_path: &Path,
+    rate: f64,
+    concurrency: usize,
+) -> EthersResult<Vec<AirdropResult>> {
+    let progress = Mutex::new(Progress::<AirdropResult>::load(progress_path)?);
+
+    let recorded = progress.lock().unwrap().rows();
+    for (row, result) in recorded {
+        match rows.get(row) {
+            Some(item) if to_recipient(item).ok() == Some(result.recipient) => {}
+            _ => {
+                return Err(Error::Internal(format!(
+                    "progress file does not match recipients at row {}",
+                    row
+                )))
+            }
+        }
+    }
+    progress::resume(contract.client().as_ref(), &progress, confirm).await?;
+
+    let unsent = rows
+        .iter()
+        .enumerate()
+        .filter(|(row, _)| needs_send(progress.lock().unwrap().get(*row)))
+        .collect::<Vec<_>>();
+
+    // Rows and the tokens we already hold are checked before anything is minted, so a bad
+    // row never costs the gas of minting the others.
+    let problems = unsent
+        .iter()
+        .filter_map(|(row, item)| {
+            validate_row(item)
+                .err()
+                .map(|e| format!("row {}: {}", row, e))
+        })
+        .collect::<Vec<_>>();
+    fail_validation(problems)?;
+    let held = unsent
+        .iter()
+        .filter(|(_, item)| item.token_id.is_some())
+        .map(|(row, item)| to_result(schema, *row, item, &BTreeMap::new()))
+        .collect::<EthersResult<Vec<_>>>()?;
+    fail_validation(check_holdings(&contract, schema, wallet, &held).await?)?;
+
+    let minted = mint_missing(&contract, schema, &rows, progress_path, concurrency).await?;
+
+    let mut todo = vec![];
+    let mut problems = vec![];
+    for (row, item) in unsent {
+        match to_result(schema, row, item, &minted) {
+            Ok(result) => todo.push(result),
+            Err(e) => problems.push(format!("row {}: {}", row, e)),
+        }
+    }
+    fail_validation(problems)?;
+    tracing::info!(rows = todo.len(), "airdropping");
+
+    let limiter = tokio::sync::Mutex::new(rate_limiter(rate));
+    stream::iter(todo)
+        .map(|result| send_row(&contract, schema, wallet, &limiter, &progress, result))
+        .buffer_unordered(concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<EthersResult<Vec<_>>>()?;
+
+    let results = progress.into_inner().unwrap().rows();
+    Ok(results.into_iter().map(|(_, result)| result).collect())
+}
+
+fn fail_validation(problems: Vec<String>) -> EthersResult<()> {
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(Error::Internal(format!(
+        "airdrop validation failed:\n{}",
+        problems.join("\n")
+    )))
+}
+
+/// Mints every `content_hash` row without a `token_id`, returning the minted id per row.
+async fn mint_missing(
+    contract: &Contract<NonceClient>,
+    schema: Schema,
+    rows: &[AirdropRow],
+    progress_path: &Path,
+    concurrency: usize,
+) -> EthersResult<BTreeMap<usize, U256>> {
+    let to_mint = rows
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.token_id.is_none())
+        .filter_map(|(row, item)| {
+            item.content_hash.clone().map(|content_hash| {
+                let amount = item.amount;
+                (
+                    row,
+                    MintRow {
+                        content_hash,
+                        amount,
+                    },
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    if to_mint.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    let results = batch_mint::mint(
+        contract.clone(),
+        schema,
+        to_mint.iter().map(|(_, item)| item.clone()).collect(),
+        &progress_path.with_extension("mint.json"),
+        concurrency,
+    )
+    .await?;
+
+    Ok(results
+        .into_iter()
+        .filter_map(|result| {
+            let (row, _) = to_mint[result.row];
+            result.token_id.map(|token_id| (row, token_id))
+        })
+        .collect())
+}
+
+fn to_recipient(item: &AirdropRow) -> EthersResult<Address> {
+    let recipient = item
+        .recipient
+        .trim()
+        .parse::<Address>()
+        .map_err(|_| Error::Internal(format!("invalid recipient: {}", item.recipient)))?;
+    if recipient.is_zero() {
+        return Err(Error::Internal("recipient is the zero address".to_string()));
+    }
+    Ok(recipient)
+}
+
+/// Checks a row on its own, before anything is minted or sent.
+fn validate_row(item: &AirdropRow) -> EthersResult<()> {
+    to_recipient(item)?;
+    match (&item.token_id, &item.content_hash) {
+        (Some(token_id), _) => to_token_id(token_id).map(|_| ()),
+        (None, Some(_)) => Ok(()),
+        (None, None) => Err(Error::Internal(
+            "either token_id or content_hash is required".to_string(),
+        )),
+    }
+}
+
+fn to_result(
+    schema: Schema,
+    row: usize,
+    item: &AirdropRow,
+    minted: &BTreeMap<usize, U256>,
+) -> EthersResult<AirdropResult> {
+    let recipient = to_recipient(item)?;
+    let token_id = match (&item.token_id, minted.get(&row)) {
+        (Some(token_id), _) => to_token_id(token_id)?,
+        (None, Some(token_id)) => *token_id,
+        (None, None) => return Err(Error::Internal("token was not minted".to_string())),
+    };
+    let amount = match schema {
+        Schema::ERC721 => U256::one(),
+        Schema::ERC1155 => U256::from(item.amount.unwrap_or(1)),
+    };
+
+    Ok(AirdropResult {
+        row,
+        recipient,
+        token_id,
+        amount,
+        tx_hash: None,
+        nonce: None,
+        confirmed: false,
+        failed: false,
+        error: None,
+    })
+}
+
+/// Problems with our holdings for the rows about to be sent: 721 tokens must be ours and
+/// appear once, and 1155 balances must cover the total amount sent per id.
+async fn check_holdings(
+    contract: &Contract<NonceClient>,
+    schema: Schema,
+    wallet: Address,
+    todo: &[AirdropResult],
+) -> EthersResult<Vec<String>> {
+    let mut problems = vec![];
+
+    match schema {
+        Schema::ERC721 => {
+            let mut seen = BTreeSet::new();
+            for result in todo {
+                if !seen.insert(result.token_id) {
+                    problems.push(format!(
+                        "row {}: token {} is airdropped twice",
+                        result.row, result.token_id
+                    ));
+                    continue;
+                }
+                let owner = contract
+                    .method::<_, Address>("ownerOf", result.token_id)?
+                    .call()
+                    .await?;
+                if owner != wallet {
+                    problems.push(format!(
+                        "row {}: token {} is owned by {:?}",
+                        result.row, result.token_id, owner
+                    ));
+                }
+            }
+        }
+        Schema::ERC1155 => {
+            let mut totals: BTreeMap<U256, U256> = BTreeMap::new();
+            for result in todo {
+                *totals.entry(result.token_id).or_default() += result.amount;
+            }
+            for (token_id, total) in totals {
+                let balance = contract
+                    .method::<_, U256>("balanceOf", (wallet, token_id))?
+                    .call()
+                    .await?;
+                if balance < total {
+                    problems.push(format!(
+                        "token {}: airdropping {} but holding {}",
+                        token_id, total, balance
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+fn rate_limiter(rate: f64) -> Interval {
+    let period = if rate > 0.0 {
+        Duration::from_secs_f64(1.0 / rate)
+    } else {
+        Duration::from_millis(1)
+    };
+    let mut limiter = interval(period);
+    limiter.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    limiter
+}
+
+#[tracing::instrument(
+    skip(contract, limiter, progress, result),
+    fields(row = result.row, recipient = ?result.recipient, token_id = %result.token_id)
+)]
+async fn send_row(
+    contract: &Contract<NonceClient>,
+    schema: Schema,
+    wallet: Address,
+    limiter: &tokio::sync::Mutex<Interval>,
+    progress: &Mutex<Progress<AirdropResult>>,
+    mut result: AirdropResult,
+) -> EthersResult<()> {
+    result.tx_hash = None;
+    result.nonce = None;
+    result.failed = false;
+    result.error = None;
+
+    limiter.lock().await.tick().await;
+    if let Err(e) = send_transfer(contract, schema, wallet, progress, &mut result).await {
+        tracing::error!(error = ?e, "transfer failed");
+        result.error = Some(format!("{:?}", e));
+    }
+
+    progress.lock().unwrap().set(result.row, result)
+}
+
+async fn send_transfer(
+    contract: &Contract<NonceClient>,
+    schema: Schema,
+    wallet: Address,
+    progress: &Mutex<Progress<AirdropResult>>,
+    result: &mut AirdropResult,
+) -> EthersResult<()> {
+    let call = match schema {
+        Schema::ERC721 => contract.method_hash::<_, H256>(
+            ethers::utils::id("safeTransferFrom(address,address,uint256,bytes)"),
+            (wallet, result.recipient, result.token_id, Bytes::default()),
+        )?,
+        Schema::ERC1155 => contract.method::<_, H256>(
+            "safeTransferFrom",
+            (
+                wallet,
+                result.recipient,
+                result.token_id,
+                result.amount,
+                Bytes::default(),
+            ),
+        )?,
+    }
+    .gas(GAS_LIMIT)
+    .gas_price(GAS_PRICE);
+
+    let tx = call.send().await?;
+    progress::track(contract.client().as_ref(), progress, result, tx, confirm).await
+}
+
+fn confirm(result: &mut AirdropResult, _: TransactionReceipt) {
+    result.confirmed = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(recipient: &str, token_id: Option<&str>, content_hash: Option<&str>) -> AirdropRow {
+        AirdropRow {
+            recipient: recipient.to_string(),
+            token_id: token_id.map(str::to_string),
+            amount: Some(3),
+            content_hash: content_hash.map(str::to_string),
+        }
+    }
+
+    const RECIPIENT: &str = "0x0E91D6613a84d7C8b72a289D8b275AF7717C3d2E";
+
+    #[test]
+    fn validates_rows_before_sending() {
+        assert!(validate_row(&row(RECIPIENT, Some("1"), None)).is_ok());
+        assert!(validate_row(&row(RECIPIENT, None, Some("Qm"))).is_ok());
+        assert!(validate_row(&row(RECIPIENT, None, None)).is_err());
+        assert!(validate_row(&row(RECIPIENT, Some("one"), None)).is_err());
+        assert!(validate_row(&row("0x1234", Some("1"), None)).is_err());
+        assert!(validate_row(&row(&format!("{:?}", Address::zero()), Some("1"), None)).is_err());
+    }
+
+    #[test]
+    fn takes_the_minted_token_for_content_hash_rows() {
+        let minted = BTreeMap::from([(4, U256::from(9))]);
+        let result = to_result(
+            Schema::ERC721,
+            4,
+            &row(RECIPIENT, None, Some("Qm")),
+            &minted,
+        )
+        .unwrap();
+        assert_eq!(result.token_id, U256::from(9));
+        assert_eq!(result.amount, U256::one());
+        assert!(needs_send(Some(&result)));
+
+        let unminted = to_result(
+            Schema::ERC721,
+            5,
+            &row(RECIPIENT, None, Some("Qm")),
+            &minted,
+        );
+        assert!(unminted.is_err());
+    }
+
+    #[test]
+    fn sends_the_row_amount_for_1155() {
+        let result = to_result(
+            Schema::ERC1155,
+            0,
+            &row(RECIPIENT, Some("0x10"), None),
+            &BTreeMap::new(),
+        )
+        .unwrap();
+        assert_eq!(result.token_id, U256::from(16));
+        assert_eq!(result.amount, U256::from(3));
+    }
+}
@@ -0,0 +1,67 @@
+use crate::event::approval_for_all_operators;
+use crate::{
+    transaction_contract, Account, Error, EthersResult, HttpProvider, ENUMERATION_CONCURRENCY,
+};
+use ethers::contract::Contract;
+use ethers::prelude::*;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use prelude::*;
+
+/// Operators `owner` currently approves for all its tokens on `contract`, an ERC-721 or
+/// ERC-1155 collection, discovered from `ApprovalForAll` logs since `from_block`.
+pub async fn approved_operators(
+    contract: &Contract<HttpProvider>,
+    owner: Address,
+    from_block: u64,
+) -> EthersResult<Vec<Address>> {
+    let operators = approval_for_all_operators(contract, owner, from_block).await?;
+    let approved: Vec<Option<Address>> = stream::iter(operators)
+        .map(|operator| async move {
+            let approved = contract
+                .method::<_, bool>("isApprovedForAll", (owner, operator))?
+                .call()
+                .await?;
+            Ok::<_, Error>(approved.then_some(operator))
+        })
+        .buffered(ENUMERATION_CONCURRENCY)
+        .try_collect()
+        .await?;
+    Ok(approved.into_iter().flatten().collect())
+}
+
+/// Revokes every operator approval each of `accounts` still grants on `contract`, signing
+/// as that account, and returns the revoked `(owner, operator)` pairs.
+pub async fn revoke_approvals(
+    contract: &Contract<HttpProvider>,
+    network: Network,
+    accounts: &[Account],
+    from_block: u64,
+) -> EthersResult<Vec<(Address, Address)>> {
+    let mut revoked = vec![];
+    for account in accounts {
+        let operators = approved_operators(contract, account.address, from_block).await?;
+        if operators.is_empty() {
+            continue;
+        }
+        let signer = transaction_contract(
+            account.secret.to_owned(),
+            contract.address(),
+            contract.abi().to_owned(),
+            network,
+        )
+        .await;
+        for operator in operators {
+            let call = signer
+                .method::<_, H256>("setApprovalForAll", (operator, false))?
+                .gas(GAS_LIMIT)
+                .gas_price(GAS_PRICE);
+            let tx = call.send().await?;
+            let receipt = tx.await?;
+
+            tracing::info!(?receipt, "transaction mined");
+
+            revoked.push((account.address, operator));
+        }
+    }
+    Ok(revoked)
+}
@@ -0,0 +1,245 @@
+use crate::event::{TransferEvent, TransferSingleEvent};
+use crate::progress::{self, needs_send, Progress, SentRow};
+use crate::{Error, EthersResult, NonceClient};
+use ethers::contract::{parse_log, Contract};
+use ethers::prelude::*;
+use futures::stream::{self, StreamExt};
+use prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// One manifest row. `amount` is only used by ERC-1155 collections and defaults to 1.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MintRow {
+    pub content_hash: String,
+    #[serde(default)]
+    pub amount: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MintResult {
+    pub row: usize,
+    pub content_hash: String,
+    pub tx_hash: Option<H256>,
+    #[serde(default)]
+    pub nonce: Option<U256>,
+    pub token_id: Option<U256>,
+    /// The transaction in `tx_hash` is known not to have minted: it reverted, or it was
+    /// dropped and its nonce reused.
+    #[serde(default)]
+    pub failed: bool,
+    pub error: Option<String>,
+}
+
+impl MintResult {
+    pub fn is_minted(&self) -> bool {
+        self.token_id.is_some()
+    }
+}
+
+impl SentRow for MintResult {
+    fn row(&self) -> usize {
+        self.row
+    }
+
+    fn tx_hash(&self) -> Option<H256> {
+        self.tx_hash
+    }
+
+    fn nonce(&self) -> Option<U256> {
+        self.nonce
+    }
+
+    fn is_done(&self) -> bool {
+        self.is_minted()
+    }
+
+    fn is_failed(&self) -> bool {
+        self.failed
+    }
+
+    fn set_sent(&mut self, tx_hash: Option<H256>, nonce: Option<U256>) {
+        self.tx_hash = tx_hash;
+        self.nonce = nonce;
+    }
+
+    fn set_failed(&mut self, failed: bool) {
+        self.failed = failed;
+    }
+
+    fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+}
+
+/// Reads a manifest from a `.json` array or a CSV file with a `content_hash[,amount]` header.
+pub fn read_manifest(path: &Path) -> EthersResult<Vec<MintRow>> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => Ok(serde_json::from_str(&fs::read_to_string(path)?)?),
+        _ => csv::Reader::from_path(path)?
+            .deserialize()
+            .collect::<Result<Vec<MintRow>, _>>()
+            .map_err(Error::from),
+    }
+}
+
+/// Mints every manifest row not already recorded in the progress file, keeping up to
+/// `concurrency` transactions in flight. Nonces come from the contract's nonce manager.
+///
+/// Rows whose transaction was sent but did not confirm, whether the run was interrupted or
+/// waiting for it failed, are looked up by hash rather than resent. They are only minted
+/// again once their transaction reverted or was dropped for good, so resuming never mints
+/// the same row twice.
+pub async fn mint(
+    contract: Contract<NonceClient>,
+    schema: Schema,
+    rows: Vec<MintRow>,
+    progress_path: &Path,
+    concurrency: usize,
+) -> EthersResult<Vec<MintResult>> {
+    let progress = Mutex::new(Progress::<MintResult>::load(progress_path)?);
+
+    let recorded = progress.lock().unwrap().rows();
+    for (row, result) in recorded {
+        match rows.get(row) {
+            Some(item) if item.content_hash == result.content_hash => {}
+            _ => {
+                return Err(Error::Internal(format!(
+                    "progress file does not match manifest at row {}",
+                    row
+                )))
+            }
+        }
+    }
+    progress::resume(contract.client().as_ref(), &progress, |result, receipt| {
+        record_mint(&contract, schema, result, receipt)
+    })
+    .await?;
+
+    let todo = rows
+        .into_iter()
+        .enumerate()
+        .filter(|(row, _)| needs_send(progress.lock().unwrap().get(*row)))
+        .collect::<Vec<_>>();
+    tracing::info!(rows = todo.len(), "minting");
+
+    stream::iter(todo)
+        .map(|(row, item)| mint_row(&contract, schema, &progress, row, item))
+        .buffer_unordered(concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<EthersResult<Vec<_>>>()?;
+
+    let results = progress.into_inner().unwrap().rows();
+    Ok(results.into_iter().map(|(_, result)| result).collect())
+}
+
+#[tracing::instrument(skip(contract, progress, item), fields(content_hash = %item.content_hash))]
+async fn mint_row(
+    contract: &Contract<NonceClient>,
+    schema: Schema,
+    progress: &Mutex<Progress<MintResult>>,
+    row: usize,
+    item: MintRow,
+) -> EthersResult<()> {
+    let mut result = MintResult {
+        row,
+        content_hash: item.content_hash.clone(),
+        tx_hash: None,
+        nonce: None,
+        token_id: None,
+        failed: false,
+        error: None,
+    };
+
+    if let Err(e) = send_mint(contract, schema, progress, &item, &mut result).await {
+        tracing::error!(error = ?e, "mint failed");
+        result.error = Some(format!("{:?}", e));
+    }
+
+    progress.lock().unwrap().set(row, result)
+}
+
+async fn send_mint(
+    contract: &Contract<NonceClient>,
+    schema: Schema,
+    progress: &Mutex<Progress<MintResult>>,
+    item: &MintRow,
+    result: &mut MintResult,
+) -> EthersResult<()> {
+    let call = match schema {
+        Schema::ERC721 => contract.method::<_, H256>("mint", item.content_hash.clone())?,
+        Schema::ERC1155 => contract.method::<_, H256>(
+            "mint",
+            (
+                item.content_hash.clone(),
+                U256::from(item.amount.unwrap_or(1)),
+            ),
+        )?,
+    }
+    .gas(GAS_LIMIT)
+    .gas_price(GAS_PRICE);
+
+    let tx = call.send().await?;
+    progress::track(
+        contract.client().as_ref(),
+        progress,
+        result,
+        tx,
+        |result, receipt| record_mint(contract, schema, result, receipt),
+    )
+    .await
+}
+
+/// Reads the minted token id out of the receipt of a successful mint.
+fn record_mint(
+    contract: &Contract<NonceClient>,
+    schema: Schema,
+    result: &mut MintResult,
+    receipt: TransactionReceipt,
+) {
+    result.token_id = receipt
+        .logs
+        .into_iter()
+        .filter(|log| log.address == contract.address())
+        .find_map(|log| match schema {
+            Schema::ERC721 => parse_log::<TransferEvent>(log)
+                .ok()
+                .filter(|event| event.from.is_zero())
+                .map(|event| event.token_id),
+            Schema::ERC1155 => parse_log::<TransferSingleEvent>(log)
+                .ok()
+                .filter(|event| event.from.is_zero())
+                .map(|event| event.id),
+        });
+    if result.token_id.is_none() {
+        result.error = Some("no mint event in receipt".to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::is_unsettled;
+
+    #[test]
+    fn reads_progress_written_before_nonces_were_recorded() {
+        let json = r#"{"row":3,"content_hash":"Qm","tx_hash":"0x0000000000000000000000000000000000000000000000000000000000000001","token_id":null,"error":"transaction dropped"}"#;
+        let old: MintResult = serde_json::from_str(json).unwrap();
+        assert!(old.nonce.is_none());
+        assert!(is_unsettled(&old));
+        assert!(!needs_send(Some(&old)));
+    }
+
+    #[test]
+    fn counts_rows_with_a_token_as_minted() {
+        let json = r#"{"row":0,"content_hash":"Qm","tx_hash":"0x0000000000000000000000000000000000000000000000000000000000000001","token_id":"0x7","error":null}"#;
+        let minted: MintResult = serde_json::from_str(json).unwrap();
+        assert!(minted.is_minted());
+        assert!(!is_unsettled(&minted));
+        assert!(!needs_send(Some(&minted)));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{http_provider, query_contract, EthersResult, HttpProvider};
+use ethers::abi::{parse_abi, Abi};
+use ethers::contract::Contract;
+use ethers::prelude::*;
+use prelude::*;
+
+pub const ERC165: [u8; 4] = [0x01, 0xff, 0xc9, 0xa7];
+pub const ERC721: [u8; 4] = [0x80, 0xac, 0x58, 0xcd];
+pub const ERC721_METADATA: [u8; 4] = [0x5b, 0x5e, 0x13, 0x9f];
+pub const ERC721_ENUMERABLE: [u8; 4] = [0x78, 0x0e, 0x9d, 0x63];
+pub const ERC1155: [u8; 4] = [0xd9, 0xb6, 0x7a, 0x26];
+pub const ERC1155_METADATA_URI: [u8; 4] = [0x0e, 0x89, 0x34, 0x1c];
+pub const ERC2981: [u8; 4] = [0x2a, 0x55, 0x20, 0x5a];
+pub const ERC4906: [u8; 4] = [0x49, 0x06, 0x49, 0x06];
+const INVALID_ID: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+
+/// What an arbitrary contract says it implements, as reported by `cli inspect`.
+#[derive(Clone, Debug, Default)]
+pub struct Capabilities {
+    pub address: Address,
+    pub is_contract: bool,
+    pub erc165: bool,
+    pub erc721: bool,
+    pub erc721_metadata: bool,
+    pub erc721_enumerable: bool,
+    pub erc1155: bool,
+    pub erc1155_metadata_uri: bool,
+    pub erc2981: bool,
+    pub erc4906: bool,
+    pub owner: Option<Address>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+}
+
+impl Capabilities {
+    pub fn schema(&self) -> Option<Schema> {
+        if self.erc721 {
+            Some(Schema::ERC721)
+        } else if self.erc1155 {
+            Some(Schema::ERC1155)
+        } else {
+            None
+        }
+    }
+}
+
+/// Probes the contract at `address` for the standard NFT interfaces plus `owner()`, `name()`
+/// and `symbol()`. Calls that revert are reported as unsupported rather than as errors.
+#[tracing::instrument(err)]
+pub async fn inspect(network: Network, address: Address) -> EthersResult<Capabilities> {
+    let mut res = Capabilities {
+        address,
+        ..Default::default()
+    };
+
+    let code = http_provider(network).get_code(address, None).await?;
+    res.is_contract = !code.is_empty();
+    if !res.is_contract {
+        return Ok(res);
+    }
+
+    let contract = contract(network, address);
+    res.erc165 = is_erc165(&contract).await?;
+    if res.erc165 {
+        res.erc721 = supports_interface(&contract, ERC721).await?;
+        res.erc721_metadata = supports_interface(&contract, ERC721_METADATA).await?;
+        res.erc721_enumerable = supports_interface(&contract, ERC721_ENUMERABLE).await?;
+        res.erc1155 = supports_interface(&contract, ERC1155).await?;
+        res.erc1155_metadata_uri = supports_interface(&contract, ERC1155_METADATA_URI).await?;
+        res.erc2981 = supports_interface(&contract, ERC2981).await?;
+        res.erc4906 = supports_interface(&contract, ERC4906).await?;
+    }
+
+    res.owner = optional(contract.method::<_, Address>("owner", ())?.call().await)?;
+    res.name = optional(contract.method::<_, String>("name", ())?.call().await)?;
+    res.symbol = optional(contract.method::<_, String>("symbol", ())?.call().await)?;
+
+    Ok(res)
+}
+
+/// Whether the contract at `address` implements ERC-165 and reports `interface_id`.
+#[tracing::instrument(err)]
+pub async fn supports(
+    network: Network,
+    address: Address,
+    interface_id: [u8; 4],
+) -> EthersResult<bool> {
+    let contract = contract(network, address);
+    Ok(is_erc165(&contract).await? && supports_interface(&contract, interface_id).await?)
+}
+
+fn contract(network: Network, address: Address) -> Contract<HttpProvider> {
+    let abi: Abi = parse_abi(&[
+        "function supportsInterface(bytes4 interfaceId) external view returns (bool)",
+        "function owner() external view returns (address)",
+        "function name() external view returns (string)",
+        "function symbol() external view returns (string)",
+    ])
+    .unwrap();
+    query_contract(address, abi, network)
+}
+
+/// ERC-165 detection as specified: `0x01ffc9a7` must be supported and `0xffffffff` must not.
+async fn is_erc165(contract: &Contract<HttpProvider>) -> EthersResult<bool> {
+    Ok(supports_interface(contract, ERC165).await?
+        && !supports_interface(contract, INVALID_ID).await?)
+}
+
+async fn supports_interface(
+    contract: &Contract<HttpProvider>,
+    interface_id: [u8; 4],
+) -> EthersResult<bool> {
+    let res = contract
+        .method::<_, bool>("supportsInterface", interface_id)?
+        .call()
+        .await;
+    Ok(optional(res)?.unwrap_or(false))
+}
+
+/// Turns a call that reverted or returned nothing decodable into `None`, keeping
+/// transport errors as errors.
+pub(crate) fn optional<T>(res: Result<T, ContractError<HttpProvider>>) -> EthersResult<Option<T>> {
+    match res {
+        Ok(v) => Ok(Some(v)),
+        Err(e) if e.is_revert() => Ok(None),
+        Err(ContractError::DecodingError(_)) | Err(ContractError::DetokenizationError(_)) => {
+            Ok(None)
+        }
+        Err(ContractError::MiddlewareError { e }) if is_revert_error(&e) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn is_revert_error(e: &ProviderError) -> bool {
+    match RpcError::as_error_response(e) {
+        Some(response) => is_revert_response(response),
+        None => false,
+    }
+}
+
+/// A JSON-RPC error for a reverted call whose message ethers does not recognise: code 3
+/// (execution error), or the generic -32000 carrying revert data.
+fn is_revert_response(e: &JsonRpcError) -> bool {
+    e.code == 3 || (e.code == -32000 && e.data.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc_error(code: i64, message: &str, data: Option<&str>) -> JsonRpcError {
+        JsonRpcError {
+            code,
+            message: message.to_string(),
+            data: data.map(|data| serde_json::Value::String(data.to_string())),
+        }
+    }
+
+    #[test]
+    fn treats_reverts_as_missing() {
+        let res: Result<bool, _> = Err(ContractError::Revert(Bytes::default()));
+        assert_eq!(optional(res).unwrap(), None);
+        assert_eq!(optional::<bool>(Ok(true)).unwrap(), Some(true));
+    }
+
+    #[test]
+    fn recognises_revert_responses_by_code() {
+        assert!(is_revert_response(&rpc_error(
+            3,
+            "execution error",
+            Some("0x")
+        )));
+        assert!(is_revert_response(&rpc_error(
+            -32000,
+            "vm error",
+            Some("0x08c379a0")
+        )));
+    }
+
+    #[test]
+    fn keeps_other_errors_even_when_they_mention_revert() {
+        assert!(!is_revert_response(&rpc_error(
+            -32000,
+            "header not found",
+            None
+        )));
+        assert!(!is_revert_response(&rpc_error(
+            -32603,
+            "cannot revert to snapshot",
+            None
+        )));
+        let res: Result<bool, _> = Err(ContractError::ContractNotDeployed);
+        assert!(optional(res).is_err());
+    }
+}
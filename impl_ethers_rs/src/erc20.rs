@@ -0,0 +1,124 @@
+use crate::{
+    query_contract, transaction_contract, Account, EthersResult, HttpProvider, SignerClient,
+};
+use ethers::abi::{parse_abi, Abi};
+use ethers::contract::Contract;
+use ethers::prelude::*;
+use prelude::*;
+
+/// Minimal ERC-20 client, used for the LINK that pays for Chainlink requests.
+#[derive(Clone, Debug)]
+pub struct Client {
+    wallet_address: Address,
+    wallet_secret: String,
+    pub address: Address,
+    abi: Abi,
+    network: Network,
+}
+
+impl Client {
+    /// Binds to the token at `address`, signing transactions with `account`.
+    pub fn new(network: Network, address: Address, account: Account) -> Self {
+        Client {
+            wallet_address: account.address,
+            wallet_secret: account.secret,
+            address,
+            abi: parse_abi(&[
+                "function decimals() external view returns (uint8)",
+                "function symbol() external view returns (string)",
+                "function balanceOf(address owner) external view returns (uint256)",
+                "function allowance(address owner, address spender) external view returns (uint256)",
+                "function transfer(address to, uint256 value) external returns (bool)",
+                "function approve(address spender, uint256 value) external returns (bool)",
+            ])
+            .unwrap(),
+            network,
+        }
+    }
+
+    pub fn wallet_address(&self) -> Address {
+        self.wallet_address
+    }
+
+    fn query(&self) -> Contract<HttpProvider> {
+        query_contract(self.address, self.abi.to_owned(), self.network)
+    }
+
+    async fn transaction(&self) -> Contract<SignerClient> {
+        transaction_contract(
+            self.wallet_secret.clone(),
+            self.address,
+            self.abi.to_owned(),
+            self.network,
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn decimals(&self) -> EthersResult<u8> {
+        let res = self.query().method::<_, u8>("decimals", ())?.call().await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn symbol(&self) -> EthersResult<String> {
+        let res = self
+            .query()
+            .method::<_, String>("symbol", ())?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn balance_of(&self, owner: Address) -> EthersResult<U256> {
+        let res = self
+            .query()
+            .method::<_, U256>("balanceOf", owner)?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn allowance(&self, owner: Address, spender: Address) -> EthersResult<U256> {
+        let res = self
+            .query()
+            .method::<_, U256>("allowance", (owner, spender))?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn transfer(&self, to: Address, amount: U256) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, bool>("transfer", (to, amount))?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn approve(&self, spender: Address, amount: U256) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, bool>("approve", (spender, amount))?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+}
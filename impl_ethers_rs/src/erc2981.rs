@@ -0,0 +1,45 @@
+use crate::erc165::{self, optional, ERC2981};
+use crate::{query_contract, EthersResult};
+use ethers::abi::parse_abi;
+use ethers::prelude::*;
+use prelude::*;
+
+#[derive(Clone, Debug)]
+pub struct Royalty {
+    pub receiver: Address,
+    pub amount: U256,
+}
+
+/// Royalty owed on a sale of `token_id` for `sale_price`, or `None` when the collection at
+/// `address` does not advertise ERC-2981 or `royaltyInfo` reverts.
+#[tracing::instrument(err)]
+pub async fn royalty_info(
+    network: Network,
+    address: Address,
+    token_id: U256,
+    sale_price: U256,
+) -> EthersResult<Option<Royalty>> {
+    if !erc165::supports(network, address, ERC2981).await? {
+        return Ok(None);
+    }
+    query_royalty_info(network, address, token_id, sale_price).await
+}
+
+/// `royaltyInfo` without the ERC-165 check, for callers that already know the collection
+/// supports it. A revert, e.g. from a collection that misreports ERC-2981, is `None`.
+pub(crate) async fn query_royalty_info(
+    network: Network,
+    address: Address,
+    token_id: U256,
+    sale_price: U256,
+) -> EthersResult<Option<Royalty>> {
+    let abi = parse_abi(&[
+        "function royaltyInfo(uint256 tokenId, uint256 salePrice) external view returns (address, uint256)",
+    ])
+    .unwrap();
+    let res = query_contract(address, abi, network)
+        .method::<_, (Address, U256)>("royaltyInfo", (token_id, sale_price))?
+        .call()
+        .await;
+    Ok(optional(res)?.map(|(receiver, amount)| Royalty { receiver, amount }))
+}
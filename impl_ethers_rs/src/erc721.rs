@@ -0,0 +1,385 @@
+use crate::airdrop::{self, AirdropResult, AirdropRow};
+use crate::approval;
+use crate::batch_mint::{self, MintResult, MintRow};
+use crate::multi_sig_wallet::proposal::Proposal;
+use crate::{
+    nonce_managed_contract, query_contract, to_count, transaction_contract, Account, Error,
+    EthersResult, HttpProvider, SignerClient, ENUMERATION_CONCURRENCY,
+};
+use async_trait::async_trait;
+use ethers::abi::{Abi, Tokenize};
+use ethers::contract::Contract;
+use ethers::prelude::*;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use prelude::*;
+use std::path::Path;
+
+/// Common ERC-721 calls shared by every 721-style client. Implementors only provide the
+/// contract location and signer; all calls have default implementations over the bundled ABI.
+#[async_trait]
+pub trait Erc721: Send + Sync {
+    fn network(&self) -> Network;
+    fn address(&self) -> Address;
+    fn abi(&self) -> &Abi;
+    fn wallet_address(&self) -> Address;
+    fn wallet_secret(&self) -> String;
+
+    fn query(&self) -> Contract<HttpProvider> {
+        query_contract(self.address(), self.abi().to_owned(), self.network())
+    }
+
+    async fn transaction(&self) -> Contract<SignerClient> {
+        transaction_contract(
+            self.wallet_secret(),
+            self.address(),
+            self.abi().to_owned(),
+            self.network(),
+        )
+        .await
+    }
+
+    /// `function(args)` as a call for the MultiSigWallet to make, for collections it owns.
+    fn proposal<T: Tokenize>(&self, function: &str, args: T) -> EthersResult<Proposal>
+    where
+        Self: Sized,
+    {
+        Proposal::call(self.address(), self.abi(), function, args)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn owner_of(&self, token_id: U256) -> EthersResult<Address> {
+        let res = self
+            .query()
+            .method::<_, Address>("ownerOf", token_id)?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn balance_of(&self, owner: Address) -> EthersResult<U256> {
+        let res = self
+            .query()
+            .method::<_, U256>("balanceOf", owner)?
+            .call()
+            .await?;
+        Ok(res)
+    }
+}
+
+/// Transfers and approvals, for collections whose tokens can move. Soul-bound collections
+/// revert on every transfer, so they only implement [`Erc721`].
+#[async_trait]
+pub trait Erc721Transferable: Erc721 {
+    #[tracing::instrument(skip(self), err)]
+    async fn transfer(&self, to: Address, token_id: U256) -> EthersResult<()> {
+        self.safe_transfer_from(self.wallet_address(), to, token_id, Bytes::default())
+            .await
+    }
+
+    /// Fails unless `from` owns `token_id` and our wallet is `from` or approved to move it,
+    /// so a transfer that would revert is never sent.
+    #[tracing::instrument(skip(self), err)]
+    async fn check_transfer(&self, from: Address, token_id: U256) -> EthersResult<()> {
+        let owner = self.owner_of(token_id).await?;
+        if owner != from {
+            return Err(Error::Internal(format!(
+                "token {} is owned by {:?}, not {:?}",
+                token_id, owner, from
+            )));
+        }
+
+        let operator = self.wallet_address();
+        if operator == from
+            || self.get_approved(token_id).await? == operator
+            || self.is_approved_for_all(from, operator).await?
+        {
+            return Ok(());
+        }
+        Err(Error::Internal(format!(
+            "{:?} is not approved to transfer token {} of {:?}",
+            operator, token_id, from
+        )))
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn transfer_from(&self, from: Address, to: Address, token_id: U256) -> EthersResult<()> {
+        self.check_transfer(from, token_id).await?;
+
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>("transferFrom", (from, to, token_id))?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+
+    /// `safeTransferFrom(address,address,uint256,bytes)`; `data` is passed to the receiver's
+    /// `onERC721Received` hook when `to` is a contract.
+    #[tracing::instrument(skip(self), err)]
+    async fn safe_transfer_from(
+        &self,
+        from: Address,
+        to: Address,
+        token_id: U256,
+        data: Bytes,
+    ) -> EthersResult<()> {
+        self.check_transfer(from, token_id).await?;
+
+        let call = self
+            .transaction()
+            .await
+            .method_hash::<_, H256>(
+                ethers::utils::id("safeTransferFrom(address,address,uint256,bytes)"),
+                (from, to, token_id, data),
+            )?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn approve(&self, to: Address, token_id: U256) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>("approve", (to, token_id))?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn get_approved(&self, token_id: U256) -> EthersResult<Address> {
+        let res = self
+            .query()
+            .method::<_, Address>("getApproved", token_id)?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn set_approval_for_all(&self, operator: Address, approved: bool) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>("setApprovalForAll", (operator, approved))?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn is_approved_for_all(&self, owner: Address, operator: Address) -> EthersResult<bool> {
+        let res = self
+            .query()
+            .method::<_, bool>("isApprovedForAll", (owner, operator))?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    /// Operators currently approved for all of `owner`'s tokens; see
+    /// [`approval::approved_operators`].
+    #[tracing::instrument(skip(self), err)]
+    async fn approved_operators(
+        &self,
+        owner: Address,
+        from_block: u64,
+    ) -> EthersResult<Vec<Address>> {
+        approval::approved_operators(&self.query(), owner, from_block).await
+    }
+
+    /// Revokes every operator approval `accounts` still grant; see
+    /// [`approval::revoke_approvals`].
+    #[tracing::instrument(skip(self, accounts), err)]
+    async fn revoke_approvals(
+        &self,
+        accounts: &[Account],
+        from_block: u64,
+    ) -> EthersResult<Vec<(Address, Address)>> {
+        approval::revoke_approvals(&self.query(), self.network(), accounts, from_block).await
+    }
+
+    /// Sends tokens to every recipient row; see [`airdrop::airdrop`].
+    #[tracing::instrument(skip(self, rows), err)]
+    async fn airdrop(
+        &self,
+        rows: Vec<AirdropRow>,
+        progress_path: &Path,
+        rate: f64,
+        concurrency: usize,
+    ) -> EthersResult<Vec<AirdropResult>> {
+        let contract = nonce_managed_contract(
+            self.wallet_secret(),
+            self.address(),
+            self.abi().to_owned(),
+            self.network(),
+        )
+        .await;
+        airdrop::airdrop(
+            contract,
+            Schema::ERC721,
+            self.wallet_address(),
+            rows,
+            progress_path,
+            rate,
+            concurrency,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+pub trait Erc721Metadata: Erc721 {
+    #[tracing::instrument(skip(self), err)]
+    async fn name(&self) -> EthersResult<String> {
+        let res = self.query().method::<_, String>("name", ())?.call().await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn symbol(&self) -> EthersResult<String> {
+        let res = self
+            .query()
+            .method::<_, String>("symbol", ())?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn token_uri(&self, token_id: U256) -> EthersResult<String> {
+        let res = self
+            .query()
+            .method::<_, String>("tokenURI", token_id)?
+            .call()
+            .await?;
+        Ok(res)
+    }
+}
+
+#[async_trait]
+pub trait Erc721Enumerable: Erc721 {
+    #[tracing::instrument(skip(self), err)]
+    async fn total_supply(&self) -> EthersResult<U256> {
+        let res = self
+            .query()
+            .method::<_, U256>("totalSupply", ())?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn token_by_index(&self, index: U256) -> EthersResult<U256> {
+        let res = self
+            .query()
+            .method::<_, U256>("tokenByIndex", index)?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    async fn token_of_owner_by_index(&self, owner: Address, index: U256) -> EthersResult<U256> {
+        let res = self
+            .query()
+            .method::<_, U256>("tokenOfOwnerByIndex", (owner, index))?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    /// Lists every token id in the collection by walking `tokenByIndex`.
+    #[tracing::instrument(skip(self), err)]
+    async fn all_tokens(&self) -> EthersResult<Vec<U256>> {
+        let total_supply = to_count(self.total_supply().await?)?;
+        stream::iter(0..total_supply)
+            .map(|index| self.token_by_index(U256::from(index)))
+            .buffered(ENUMERATION_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    /// Lists every token id held by `owner` by walking `tokenOfOwnerByIndex`.
+    #[tracing::instrument(skip(self), err)]
+    async fn tokens_of_owner(&self, owner: Address) -> EthersResult<Vec<U256>> {
+        let balance = to_count(self.balance_of(owner).await?)?;
+        stream::iter(0..balance)
+            .map(|index| self.token_of_owner_by_index(owner, U256::from(index)))
+            .buffered(ENUMERATION_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+}
+
+/// Read surface of an enumerable ERC-721 collection, usable as `dyn Nft721`.
+pub trait Nft721: Erc721Metadata + Erc721Enumerable {}
+
+impl<T: Erc721Metadata + Erc721Enumerable> Nft721 for T {}
+
+/// [`Nft721`] plus transfers and approvals, usable as `dyn TransferableNft721`.
+pub trait TransferableNft721: Nft721 + Erc721Transferable {}
+
+impl<T: Nft721 + Erc721Transferable> TransferableNft721 for T {}
+
+/// Collections owned by our wallet that mint a token from a content hash via `mint(string)`.
+#[async_trait]
+pub trait Erc721Mintable: Erc721 {
+    #[tracing::instrument(skip(self), err)]
+    async fn mint(&self, hash: String) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>("mint", hash)?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+
+    /// Mints every row of a manifest; see [`batch_mint::mint`].
+    #[tracing::instrument(skip(self, rows), err)]
+    async fn batch_mint(
+        &self,
+        rows: Vec<MintRow>,
+        progress_path: &Path,
+        concurrency: usize,
+    ) -> EthersResult<Vec<MintResult>> {
+        let contract = nonce_managed_contract(
+            self.wallet_secret(),
+            self.address(),
+            self.abi().to_owned(),
+            self.network(),
+        )
+        .await;
+        batch_mint::mint(contract, Schema::ERC721, rows, progress_path, concurrency).await
+    }
+}
@@ -1,54 +1,58 @@
-use crate::{nft_721, nft_market, EthersResult};
+use crate::{nft_721, nft_market, ws_provider, EthersResult, HttpProvider};
 use ethers::contract::Contract;
 use ethers::prelude::*;
 use prelude::*;
+use std::collections::BTreeSet;
 use std::env;
 use std::sync::Arc;
+use std::time::Instant;
+use tracing::Instrument;
 
 pub async fn watch_nft_transfer_event() -> EthersResult<()> {
     let nft = nft_721::client::Client::new(Network::Polygon);
     let market = nft_market::client::Client::new(Network::Polygon);
 
     let provider = Arc::new(
-        Provider::<Ws>::connect(
+        ws_provider(
             env::var("POLYGON_WS_URL")
                 .expect("POLYGON_WS_URL must be set")
                 .as_str(),
         )
         .await?,
     );
-    let contract = Contract::new(nft.address, nft.abi, provider);
+    let contract = Contract::new(nft.address, nft.abi.clone(), provider);
 
     let event = contract.event_for_name::<TransferEvent>("Transfer")?;
     let mut stream = event.stream().await?;
-    println!("{}", "waiting transfer event");
+    tracing::info!("waiting transfer event");
     while let Some(event) = stream.next().await {
         match event {
             Ok(event) => {
-                println!(
-                    "transfer: from: {}, to: {}, token_id: {}",
-                    event.from, event.to, event.token_id
+                let span = tracing::info_span!(
+                    "transfer_event",
+                    from = ?event.from,
+                    to = ?event.to,
+                    token_id = %event.token_id
                 );
+                let started = Instant::now();
+                let res = handle_transfer_event(&nft, &market, event)
+                    .instrument(span)
+                    .await;
 
-                let key = format!("{:?}#{}", nft.address, event.token_id);
-                println!("target: {}", key);
-
-                let order_keys = market.get_sell_order_keys().await?;
-                for key in order_keys.clone() {
-                    println!("key: {}", key);
-                }
-
-                if order_keys.contains(&key) {
-                    market
-                        .cancel_order_by_admin(
-                            format!("{:?}", nft.address),
-                            event.token_id.as_u128(),
-                        )
-                        .await?;
+                metrics::increment_counter!("events_handled_total", "event" => "Transfer");
+                metrics::histogram!(
+                    "event_handle_duration_seconds",
+                    started.elapsed().as_secs_f64(),
+                    "event" => "Transfer"
+                );
+                if let Err(e) = res {
+                    metrics::increment_counter!("event_errors_total", "event" => "Transfer");
+                    return Err(e);
                 }
             }
             Err(e) => {
-                eprintln!("error while processing event: {:?}", e);
+                metrics::increment_counter!("event_errors_total", "event" => "Transfer");
+                tracing::error!(error = ?e, "error while processing event");
             }
         }
     }
@@ -56,6 +60,50 @@ pub async fn watch_nft_transfer_event() -> EthersResult<()> {
     Ok(())
 }
 
+async fn handle_transfer_event(
+    nft: &nft_721::client::Client,
+    market: &nft_market::client::Client,
+    event: TransferEvent,
+) -> EthersResult<()> {
+    let key = format!("{:?}#{}", nft.address, event.token_id);
+    tracing::info!("target: {}", key);
+
+    let order_keys = market.get_sell_order_keys().await?;
+    for key in order_keys.iter() {
+        tracing::debug!("key: {}", key);
+    }
+
+    if order_keys.contains(&key) {
+        market
+            .cancel_order_by_admin(format!("{:?}", nft.address), event.token_id)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Every operator `owner` has ever granted or revoked approval for all tokens on `contract`,
+/// according to its `ApprovalForAll` logs since `from_block`.
+pub async fn approval_for_all_operators(
+    contract: &Contract<HttpProvider>,
+    owner: Address,
+    from_block: u64,
+) -> EthersResult<Vec<Address>> {
+    let events = contract
+        .event::<ApprovalForAllEvent>()
+        .topic1(owner)
+        .from_block(from_block)
+        .query()
+        .await?;
+
+    Ok(events
+        .into_iter()
+        .map(|event| event.operator)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect())
+}
+
 #[derive(EthEvent)]
 #[ethevent(abi = "Transfer(address,address,uint256)")]
 pub struct TransferEvent {
@@ -66,3 +114,121 @@ pub struct TransferEvent {
     #[ethevent(indexed, name = "tokenId")]
     pub token_id: U256,
 }
+
+#[derive(EthEvent)]
+#[ethevent(abi = "TransferSingle(address,address,address,uint256,uint256)")]
+pub struct TransferSingleEvent {
+    #[ethevent(indexed, name = "operator")]
+    pub operator: Address,
+    #[ethevent(indexed, name = "from")]
+    pub from: Address,
+    #[ethevent(indexed, name = "to")]
+    pub to: Address,
+    pub id: U256,
+    pub value: U256,
+}
+
+#[derive(EthEvent)]
+#[ethevent(abi = "ApprovalForAll(address,address,bool)")]
+pub struct ApprovalForAllEvent {
+    #[ethevent(indexed, name = "owner")]
+    pub owner: Address,
+    #[ethevent(indexed, name = "operator")]
+    pub operator: Address,
+    pub approved: bool,
+}
+
+#[derive(EthEvent)]
+#[ethevent(abi = "TransferBatch(address,address,address,uint256[],uint256[])")]
+pub struct TransferBatchEvent {
+    #[ethevent(indexed, name = "operator")]
+    pub operator: Address,
+    #[ethevent(indexed, name = "from")]
+    pub from: Address,
+    #[ethevent(indexed, name = "to")]
+    pub to: Address,
+    pub ids: Vec<U256>,
+    pub values: Vec<U256>,
+}
+
+#[derive(EthEvent)]
+#[ethevent(abi = "ChainlinkRequested(bytes32)")]
+pub struct ChainlinkRequestedEvent {
+    #[ethevent(indexed, name = "id")]
+    pub id: [u8; 32],
+}
+
+#[derive(EthEvent)]
+#[ethevent(abi = "ChainlinkFulfilled(bytes32)")]
+pub struct ChainlinkFulfilledEvent {
+    #[ethevent(indexed, name = "id")]
+    pub id: [u8; 32],
+}
+
+#[derive(EthEvent)]
+#[ethevent(
+    abi = "OracleRequest(bytes32,address,bytes32,uint256,address,bytes4,uint256,uint256,bytes)"
+)]
+pub struct OracleRequestEvent {
+    #[ethevent(indexed, name = "specId")]
+    pub spec_id: [u8; 32],
+    pub requester: Address,
+    pub request_id: [u8; 32],
+    pub payment: U256,
+    pub callback_addr: Address,
+    pub callback_function_id: [u8; 4],
+    pub cancel_expiration: U256,
+    pub data_version: U256,
+    pub data: Bytes,
+}
+
+#[derive(EthEvent)]
+#[ethevent(abi = "Submission(uint256)")]
+pub struct SubmissionEvent {
+    #[ethevent(indexed, name = "transactionId")]
+    pub transaction_id: U256,
+}
+
+#[derive(EthEvent)]
+#[ethevent(abi = "Confirmation(address,uint256)")]
+pub struct ConfirmationEvent {
+    #[ethevent(indexed, name = "sender")]
+    pub sender: Address,
+    #[ethevent(indexed, name = "transactionId")]
+    pub transaction_id: U256,
+}
+
+#[derive(EthEvent)]
+#[ethevent(abi = "Execution(uint256)")]
+pub struct ExecutionEvent {
+    #[ethevent(indexed, name = "transactionId")]
+    pub transaction_id: U256,
+}
+
+#[derive(EthEvent)]
+#[ethevent(abi = "ExecutionFailure(uint256)")]
+pub struct ExecutionFailureEvent {
+    #[ethevent(indexed, name = "transactionId")]
+    pub transaction_id: U256,
+}
+
+#[derive(EthEvent)]
+#[ethevent(abi = "OwnerAdded(address)")]
+pub struct OwnerAddedEvent {
+    #[ethevent(indexed, name = "owner")]
+    pub owner: Address,
+}
+
+#[derive(EthEvent)]
+#[ethevent(abi = "OwnerRemoved(address)")]
+pub struct OwnerRemovedEvent {
+    #[ethevent(indexed, name = "owner")]
+    pub owner: Address,
+}
+
+#[derive(EthEvent)]
+#[ethevent(abi = "RequirementChanged(uint256)")]
+pub struct RequirementChangedEvent {
+    #[ethevent(name = "required")]
+    pub required: U256,
+}
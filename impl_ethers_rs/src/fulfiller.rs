@@ -0,0 +1,207 @@
+use crate::event::OracleRequestEvent;
+use crate::oracle::client::Client;
+use crate::{Error, EthersResult};
+use ethers::abi::Token;
+use ethers::prelude::*;
+use prelude::clock::Clock;
+use prelude::*;
+use std::collections::HashSet;
+use std::env;
+
+/// Read from the environment:
+///
+/// - `FULFILLER_JOB_ID`: only answer requests for this job (hyphens optional); all when unset.
+/// - `FULFILLER_FROM_BLOCK`: first block scanned for requests, defaulting to the latest block.
+/// - `FULFILLER_POLL_SECS`: how often new blocks are scanned (5).
+///
+/// The answered time comes from [`Clock::from_env`], like the time adapter server's.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub job_id: Option<String>,
+    pub from_block: Option<u64>,
+    pub poll_interval: std::time::Duration,
+    pub clock: Clock,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        let job_id = env::var("FULFILLER_JOB_ID")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| v.replace('-', ""));
+        let from_block = env::var("FULFILLER_FROM_BLOCK")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                v.parse::<u64>()
+                    .expect("FULFILLER_FROM_BLOCK must be a number")
+            });
+        let poll_secs = env::var("FULFILLER_POLL_SECS")
+            .map(|v| {
+                v.parse::<u64>()
+                    .expect("FULFILLER_POLL_SECS must be a number")
+            })
+            .unwrap_or(5);
+
+        Config {
+            job_id,
+            from_block,
+            poll_interval: std::time::Duration::from_secs(poll_secs),
+            clock: Clock::from_env(),
+        }
+    }
+
+    /// Chainlink passes the job id to the oracle as the ASCII bytes of its 32 hex digits.
+    fn accepts(&self, spec_id: [u8; 32]) -> bool {
+        match &self.job_id {
+            Some(job_id) => job_id.as_bytes() == spec_id,
+            None => true,
+        }
+    }
+}
+
+/// Stands in for a Chainlink node running the time adapter job: watches the Ethereum oracle
+/// for `OracleRequest` logs and answers each with the current time via
+/// `fulfillOracleRequest2`. Runs forever.
+///
+/// Our node account (`CHAINLINK_NODE_SECRET`) must be an authorized sender of the oracle.
+/// A request whose fulfillment fails is logged and not retried, as a node would.
+pub async fn run(config: Config) -> EthersResult<()> {
+    let oracle = Client::new(Network::Ethereum);
+    if !oracle.is_authorized_sender().await? {
+        return Err(Error::Internal(format!(
+            "{:?} is not an authorized sender of oracle {:?}",
+            oracle.wallet_address, oracle.address
+        )));
+    }
+
+    let mut next_block = match config.from_block {
+        Some(block) => block,
+        None => oracle.block_number().await?,
+    };
+    let mut handled = HashSet::new();
+    tracing::info!(?config, next_block, "waiting oracle requests");
+
+    loop {
+        match poll(&oracle, &config, next_block, &mut handled).await {
+            Ok(block) => next_block = block,
+            Err(e) => {
+                metrics::increment_counter!("fulfiller_errors_total");
+                tracing::error!("error: {:?}", e);
+            }
+        }
+        tokio::time::sleep(config.poll_interval).await;
+    }
+}
+
+/// Answers every request from `from_block` to the latest block and returns the next block
+/// to scan.
+async fn poll(
+    oracle: &Client,
+    config: &Config,
+    from_block: u64,
+    handled: &mut HashSet<[u8; 32]>,
+) -> EthersResult<u64> {
+    let latest = oracle.block_number().await?;
+    if latest < from_block {
+        return Ok(from_block);
+    }
+
+    for request in oracle.oracle_requests(from_block, latest).await? {
+        if !config.accepts(request.spec_id) || !handled.insert(request.request_id) {
+            continue;
+        }
+
+        let request_id = H256::from(request.request_id);
+        match fulfill(oracle, &config.clock, &request).await {
+            Ok(()) => {
+                metrics::increment_counter!("fulfiller_fulfilled_total");
+                tracing::info!(?request_id, "request fulfilled");
+            }
+            Err(e) => {
+                metrics::increment_counter!("fulfiller_errors_total");
+                tracing::error!(?request_id, "fulfillment failed: {:?}", e);
+            }
+        }
+    }
+
+    Ok(latest + 1)
+}
+
+async fn fulfill(oracle: &Client, clock: &Clock, request: &OracleRequestEvent) -> EthersResult<()> {
+    let params = decode_params(&request.data)?;
+    let (now, timestamp) = clock.response();
+    tracing::info!(requester = ?request.requester, %params, %now, timestamp, "answering");
+
+    let data = ethers::abi::encode(&[
+        Token::FixedBytes(request.request_id.to_vec()),
+        Token::String(now),
+        Token::Uint(U256::from(timestamp)),
+    ]);
+    oracle
+        .fulfill_oracle_request_2(request, Bytes::from(data))
+        .await
+}
+
+/// Decodes the request parameters a consumer added with `req.add`, which Chainlink sends as
+/// the entries of an indefinite-length CBOR map without its opening and closing markers.
+pub fn decode_params(data: &[u8]) -> EthersResult<serde_json::Value> {
+    let mut buf = Vec::with_capacity(data.len() + 2);
+    buf.push(0xbf);
+    buf.extend_from_slice(data);
+    buf.push(0xff);
+
+    ciborium::de::from_reader(buf.as_slice())
+        .map_err(|e| Error::Internal(format!("cbor decode error: {:?}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A short CBOR text string, as `BufferChainlink.encodeString` writes it.
+    fn text(v: &str) -> Vec<u8> {
+        let mut buf = vec![0x60 + v.len() as u8];
+        buf.extend_from_slice(v.as_bytes());
+        buf
+    }
+
+    #[test]
+    fn decodes_no_params() {
+        assert_eq!(decode_params(&[]).unwrap(), json!({}));
+    }
+
+    #[test]
+    fn decodes_string_params() {
+        let data = [text("tz"), text("Asia/Tokyo"), text("fmt"), text("rfc3339")].concat();
+        assert_eq!(
+            decode_params(&data).unwrap(),
+            json!({"tz": "Asia/Tokyo", "fmt": "rfc3339"})
+        );
+    }
+
+    #[test]
+    fn decodes_params_ending_in_0xff() {
+        // `req.addUint("times", 255)` ends the buffer with the uint8 header and 0xff.
+        let data = [text("times"), vec![0x18, 0xff]].concat();
+        assert_eq!(*data.last().unwrap(), 0xff);
+        assert_eq!(decode_params(&data).unwrap(), json!({"times": 255}));
+    }
+
+    #[test]
+    fn decodes_int_params() {
+        // `req.addInt("offset", -1)` and `req.addUint("hours", 9)`.
+        let data = [text("offset"), vec![0x20], text("hours"), vec![0x09]].concat();
+        assert_eq!(
+            decode_params(&data).unwrap(),
+            json!({"offset": -1, "hours": 9})
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_params() {
+        let data = [text("tz"), vec![0x6a, b'A', b's']].concat();
+        assert!(decode_params(&data).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::reveal_nft_721::client::fulfill_update_time_selector;
+use crate::{Error, EthersResult};
+use ethers::abi::{Abi, AbiParser};
+use ethers::prelude::*;
+use prelude::*;
+
+const ORACLE_REQUEST_ABI: &str = "OracleRequest(bytes32 indexed specId, address requester, bytes32 requestId, uint256 payment, address callbackAddr, bytes4 callbackFunctionId, uint256 cancelExpiration, uint256 dataVersion, bytes data)";
+const RESPONSE_ABI: &str = "(bytes32 requestId, string now, uint256 timestamp)";
+const FULFILL_ABI: &str = "fulfillOracleRequest2(bytes32 requestId, uint256 payment, address callbackAddress, bytes4 callbackFunctionId, uint256 expiration, bytes calldata data)";
+
+/// Direct-request job spec that answers `RevealNft721.updateTime` through the time adapter
+/// bridge, for the oracle and bridge configured for `network`.
+///
+/// `job_id` is the id set with `setTimeAdapterJobId` (hyphens optional); the node picks its
+/// own when `None`.
+pub fn time_adapter(network: Network, job_id: Option<String>) -> EthersResult<String> {
+    check_response_abi()?;
+    let oracle =
+        ethers::utils::to_checksum(&network.oracle_address().parse::<Address>().unwrap(), None);
+    let bridge = network.time_adapter_bridge_name();
+    let external_job_id = match job_id {
+        Some(job_id) => format!("externalJobID = \"{}\"\n", to_uuid(&job_id)?),
+        None => String::new(),
+    };
+
+    Ok(format!(
+        r#"{external_job_id}contractAddress = "{oracle}" # Oracle Address
+name = "TimeAdapter"
+observationSource = """
+  decode_log [
+    type="ethabidecodelog"
+    abi="{ORACLE_REQUEST_ABI}"
+    data="$(jobRun.logData)"
+    topics="$(jobRun.logTopics)"
+  ]
+  decode_cbor [type=cborparse data="$(decode_log.data)"]
+
+  fetch [
+    type="bridge"
+    name="{bridge}"
+    requestData="{{\\"id\\": $(jobSpec.externalJobID), \\"params\\": $(decode_cbor.params)}}"
+  ]
+
+  decode_log -> decode_cbor -> fetch
+
+  data_now [type="jsonparse" path="data,now" data="$(fetch)"]
+  data_timestamp [type="jsonparse" path="data,timestamp" data="$(fetch)"]
+
+  fetch -> data_now
+  fetch -> data_timestamp
+
+  encode_data [
+    type="ethabiencode"
+    abi="{RESPONSE_ABI}"
+    data="{{\\"requestId\\": $(decode_log.requestId), \\"now\\": $(data_now), \\"timestamp\\": $(data_timestamp)}}"
+  ]
+
+  data_now -> encode_data
+  data_timestamp -> encode_data
+
+  encode_tx [
+    type="ethabiencode"
+    abi="{FULFILL_ABI}"
+    data="{{\\"requestId\\": $(decode_log.requestId), \\"payment\\": $(decode_log.payment), \\"callbackAddress\\": $(decode_log.callbackAddr), \\"callbackFunctionId\\": $(decode_log.callbackFunctionId), \\"expiration\\": $(decode_log.cancelExpiration), \\"data\\": $(encode_data)}}"
+  ]
+  submit_tx [
+    type="ethtx" to="{oracle}"
+    data="$(encode_tx)"
+  ]
+
+  encode_data -> encode_tx -> submit_tx
+"""
+schemaVersion = 1
+type = "directrequest"
+"#
+    ))
+}
+
+/// The node encodes its answer with `RESPONSE_ABI`, so the spec is only usable while that
+/// still matches `fulfillUpdateTime` in the bundled reveal ABI.
+fn check_response_abi() -> EthersResult<()> {
+    let reveal: Abi = serde_json::from_str(include_str!("reveal_nft_721/abi.json").trim())
+        .map_err(|e| Error::Internal(e.to_string()))?;
+    let callback = reveal
+        .function("fulfillUpdateTime")
+        .map_err(|e| Error::Internal(e.to_string()))?;
+    let response = AbiParser::default()
+        .parse_function(&format!("function fulfillUpdateTime{}", RESPONSE_ABI))
+        .map_err(|e| Error::Internal(e.to_string()))?;
+    let selector = callback.short_signature();
+    if response.short_signature() != selector || selector != fulfill_update_time_selector() {
+        return Err(Error::Internal(format!(
+            "{} does not match fulfillUpdateTime in the reveal ABI",
+            RESPONSE_ABI
+        )));
+    }
+    Ok(())
+}
+
+/// `externalJobID` wants the job id as a UUID, while the contract stores it without hyphens.
+fn to_uuid(job_id: &str) -> EthersResult<String> {
+    let hex = job_id.replace('-', "").to_lowercase();
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::Internal(format!("invalid job id: {}", job_id)));
+    }
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::OracleRequestEvent;
+    use ethers::contract::EthEvent;
+
+    #[test]
+    fn decode_log_matches_oracle_request() {
+        let event = AbiParser::default()
+            .parse_event(&format!("event {}", ORACLE_REQUEST_ABI))
+            .unwrap();
+        assert_eq!(event.signature(), OracleRequestEvent::signature());
+    }
+
+    #[test]
+    fn encode_data_matches_fulfill_update_time() {
+        check_response_abi().unwrap();
+    }
+
+    #[test]
+    fn encode_tx_is_fulfill_oracle_request_2() {
+        let fulfill = AbiParser::default()
+            .parse_function(&format!("function {}", FULFILL_ABI))
+            .unwrap();
+        assert_eq!(
+            fulfill.short_signature()[..],
+            ethers::utils::id(
+                "fulfillOracleRequest2(bytes32,uint256,address,bytes4,uint256,bytes)"
+            )
+        );
+    }
+
+    #[test]
+    fn formats_job_ids_as_uuids() {
+        assert_eq!(
+            to_uuid("371DDF3B2F034EE2BFEA97EBE6398165").unwrap(),
+            "371ddf3b-2f03-4ee2-bfea-97ebe6398165"
+        );
+        assert_eq!(
+            to_uuid("371ddf3b-2f03-4ee2-bfea-97ebe6398165").unwrap(),
+            "371ddf3b-2f03-4ee2-bfea-97ebe6398165"
+        );
+        assert!(to_uuid("371ddf3b").is_err());
+        assert!(to_uuid("zz1ddf3b2f034ee2bfea97ebe6398165").is_err());
+    }
+}
@@ -0,0 +1,430 @@
+use crate::reveal_nft_721::client::{
+    fulfill_update_time_selector, ChainlinkRequest, Client, REVEAL_END_HOUR, REVEAL_START_HOUR,
+};
+use crate::{link_monitor, Account, EthersResult};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ethers::types::U256;
+use prelude::*;
+use std::env;
+
+/// When the keeper asks the oracle for a fresh time, read from the environment:
+///
+/// - `KEEPER_SCHEDULE`: comma separated UTC hours to call `updateTime` at, defaulting to the
+///   reveal window boundaries so the reveal state flips on time.
+/// - `KEEPER_MAX_AGE_SECS`: also call it whenever the last oracle response is older than this.
+/// - `KEEPER_POLL_SECS`: how often the chain is checked (60).
+/// - `KEEPER_REQUEST_TIMEOUT_SECS`: how long to wait for an answer when the oracle does not
+///   report a cancel expiration (300, the Operator's own expiry).
+///
+/// The reveal contract's LINK is checked before every request and topped up when
+/// `LINK_TOP_UP_AMOUNT` is set; see [`link_monitor::Config`].
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub schedule: Vec<u32>,
+    pub max_age: Option<Duration>,
+    pub poll_interval: std::time::Duration,
+    pub request_timeout: Duration,
+    pub link: link_monitor::Config,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        let schedule = match env::var("KEEPER_SCHEDULE") {
+            Ok(v) => v
+                .split(',')
+                .map(|hour| {
+                    let hour = hour
+                        .trim()
+                        .parse::<u32>()
+                        .expect("KEEPER_SCHEDULE must be a list of hours");
+                    assert!(hour < 24, "KEEPER_SCHEDULE hours must be below 24");
+                    hour
+                })
+                .collect(),
+            Err(_) => vec![REVEAL_START_HOUR, REVEAL_END_HOUR],
+        };
+        let max_age = env::var("KEEPER_MAX_AGE_SECS")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                Duration::seconds(
+                    v.parse::<i64>()
+                        .expect("KEEPER_MAX_AGE_SECS must be a number"),
+                )
+            });
+        let poll_secs = env::var("KEEPER_POLL_SECS")
+            .map(|v| v.parse::<u64>().expect("KEEPER_POLL_SECS must be a number"))
+            .unwrap_or(60);
+        let request_timeout_secs = env::var("KEEPER_REQUEST_TIMEOUT_SECS")
+            .map(|v| {
+                v.parse::<i64>()
+                    .expect("KEEPER_REQUEST_TIMEOUT_SECS must be a number")
+            })
+            .unwrap_or(300);
+
+        Config {
+            schedule,
+            max_age,
+            poll_interval: std::time::Duration::from_secs(poll_secs),
+            request_timeout: Duration::seconds(request_timeout_secs),
+            link: link_monitor::Config::from_env(),
+        }
+    }
+}
+
+/// An `updateTime` request the keeper is waiting on.
+#[derive(Clone, Debug)]
+struct Pending {
+    request: ChainlinkRequest,
+    expires_at: DateTime<Utc>,
+}
+
+/// Keeps the reveal contract's oracle time fresh on Ethereum. Runs forever.
+///
+/// `updateTime` is called when a scheduled hour passes or the last response is older than
+/// `max_age`. The resulting request is tracked until `fulfillUpdateTime` lands; once it
+/// expires unanswered it is cancelled with `cancelRequest` (refunding the LINK) and retried.
+/// Read failures are logged and retried on the next poll rather than stopping the keeper; a
+/// failed `updateTime` is retried with an exponential backoff instead.
+pub async fn run(config: Config) -> EthersResult<()> {
+    let client = Client::new(Network::Ethereum);
+    let mut state = State::default();
+    let mut last_poll = Utc::now();
+    tracing::info!(?config, "keeper started");
+
+    loop {
+        let now = Utc::now();
+        match tick(&client, &config, &mut state, last_poll, now).await {
+            Ok(true) => last_poll = now,
+            Ok(false) => {}
+            Err(e) => {
+                metrics::increment_counter!("keeper_errors_total");
+                tracing::error!("error: {:?}", e);
+            }
+        }
+        tokio::time::sleep(config.poll_interval).await;
+    }
+}
+
+/// Longest wait between attempts after `updateTime` keeps failing.
+const MAX_BACKOFF_SECS: i64 = 3600;
+
+/// What the keeper remembers between polls.
+#[derive(Clone, Debug, Default)]
+struct State {
+    pending: Option<Pending>,
+    /// A request is owed even though no scheduled hour is passing: the last one expired,
+    /// failed, or could not be paid for.
+    retry: bool,
+    /// Consecutive `updateTime` failures, and when the next attempt may be made.
+    failures: u32,
+    backoff_until: Option<DateTime<Utc>>,
+}
+
+/// What became of the pending request as of a poll.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PendingOutcome {
+    Fulfilled,
+    Expired,
+    Waiting,
+}
+
+impl State {
+    fn pending_outcome(&self, fulfilled: bool, now: DateTime<Utc>) -> Option<PendingOutcome> {
+        let pending = self.pending.as_ref()?;
+        Some(if fulfilled {
+            PendingOutcome::Fulfilled
+        } else if now >= pending.expires_at {
+            PendingOutcome::Expired
+        } else {
+            PendingOutcome::Waiting
+        })
+    }
+
+    /// Why `updateTime` is due in `(last_poll, now]` without asking the chain, if it is.
+    fn reason(
+        &self,
+        schedule: &[u32],
+        last_poll: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<&'static str> {
+        if self.retry {
+            Some("retry")
+        } else if scheduled_between(schedule, last_poll, now) {
+            Some("schedule")
+        } else {
+            None
+        }
+    }
+
+    fn is_backing_off(&self, now: DateTime<Utc>) -> bool {
+        matches!(self.backoff_until, Some(until) if now < until)
+    }
+
+    fn submitted(&mut self, pending: Pending) {
+        self.pending = Some(pending);
+        self.retry = false;
+        self.failures = 0;
+        self.backoff_until = None;
+    }
+
+    /// The request is still owed, but not before a backoff that doubles with every
+    /// consecutive failure, starting at `poll_interval`.
+    fn failed(&mut self, now: DateTime<Utc>, poll_interval: std::time::Duration) {
+        self.retry = true;
+        self.failures += 1;
+        let poll_secs = poll_interval.as_secs().max(1) as i64;
+        let backoff = poll_secs
+            .checked_shl(self.failures - 1)
+            .filter(|secs| *secs <= MAX_BACKOFF_SECS)
+            .unwrap_or(MAX_BACKOFF_SECS);
+        self.backoff_until = Some(now + Duration::seconds(backoff));
+    }
+}
+
+/// Returns whether everything due in `(last_poll, now]` was handled, i.e. whether the next
+/// poll may start from `now`. A failed `updateTime` counts as handled: it is retried after
+/// a backoff rather than on every poll.
+async fn tick(
+    client: &Client,
+    config: &Config,
+    state: &mut State,
+    last_poll: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> EthersResult<bool> {
+    if let Some(current) = state.pending.clone() {
+        let request = &current.request;
+        let fulfilled = client.is_fulfilled(request.id, request.block).await?;
+        match state.pending_outcome(fulfilled, now) {
+            Some(PendingOutcome::Fulfilled) => {
+                metrics::increment_counter!("keeper_fulfilled_total");
+                tracing::info!(request_id = ?request.id, "request fulfilled");
+                state.pending = None;
+            }
+            Some(PendingOutcome::Expired) => {
+                match request.expiration {
+                    Some(expiration) => {
+                        client
+                            .cancel_request(request.id, fulfill_update_time_selector(), expiration)
+                            .await?;
+                        metrics::increment_counter!("keeper_cancelled_total");
+                        tracing::warn!(request_id = ?request.id, "request expired, cancelled");
+                    }
+                    None => {
+                        tracing::warn!(request_id = ?request.id, "request timed out, not cancellable");
+                    }
+                }
+                state.pending = None;
+                state.retry = true;
+            }
+            // A scheduled hour passing meanwhile still needs a request of its own.
+            Some(PendingOutcome::Waiting) | None => return Ok(false),
+        }
+    }
+
+    if state.is_backing_off(now) {
+        return Ok(true);
+    }
+    let reason = match state.reason(&config.schedule, last_poll, now) {
+        Some(reason) => Some(reason),
+        None if is_stale(client, config.max_age, now).await? => Some("stale"),
+        None => None,
+    };
+    let reason = match reason {
+        Some(reason) => reason,
+        None => return Ok(true),
+    };
+
+    let owner = Account::from_env();
+    let mut link = link_monitor::check(client, owner.clone(), &config.link).await?;
+    link_monitor::top_up(client, owner, &config.link, &mut link).await?;
+    if link.requests_left().is_zero() {
+        tracing::warn!(
+            reason,
+            "not enough LINK for updateTime, waiting for a top-up"
+        );
+        state.retry = true;
+        return Ok(true);
+    }
+
+    tracing::info!(reason, "calling updateTime");
+    let request = match client.update_time().await {
+        Ok(request) => request,
+        Err(e) => {
+            state.failed(now, config.poll_interval);
+            metrics::increment_counter!("keeper_errors_total");
+            tracing::error!(
+                reason,
+                failures = state.failures,
+                backoff_until = ?state.backoff_until,
+                "updateTime failed: {:?}",
+                e
+            );
+            return Ok(true);
+        }
+    };
+    metrics::increment_counter!("keeper_requests_total", "reason" => reason);
+
+    let expires_at = request
+        .expiration
+        .and_then(to_datetime)
+        .unwrap_or_else(|| now + config.request_timeout);
+    tracing::info!(request_id = ?request.id, %expires_at, "waiting for fulfillment");
+    state.submitted(Pending {
+        request,
+        expires_at,
+    });
+
+    Ok(true)
+}
+
+/// Whether any of the scheduled UTC hours started in `(from, to]`.
+fn scheduled_between(schedule: &[u32], from: DateTime<Utc>, to: DateTime<Utc>) -> bool {
+    let mut day = from.date_naive();
+    while day <= to.date_naive() {
+        for hour in schedule {
+            let at = Utc.from_utc_datetime(&day.and_hms_opt(*hour, 0, 0).unwrap());
+            if from < at && at <= to {
+                return true;
+            }
+        }
+        day = day.succ_opt().unwrap();
+    }
+    false
+}
+
+/// Whether the last oracle response is older than `max_age`, or missing altogether. A
+/// timestamp too large to be a time counts as stale too.
+async fn is_stale(
+    client: &Client,
+    max_age: Option<Duration>,
+    now: DateTime<Utc>,
+) -> EthersResult<bool> {
+    let max_age = match max_age {
+        Some(max_age) => max_age,
+        None => return Ok(false),
+    };
+    let (_, timestamp) = client.time_adapter_response().await?;
+    if timestamp.is_zero() {
+        return Ok(true);
+    }
+    match to_datetime(timestamp) {
+        Some(answered_at) => Ok(now - answered_at > max_age),
+        None => {
+            tracing::warn!(%timestamp, "oracle timestamp is out of range");
+            Ok(true)
+        }
+    }
+}
+
+/// Unix seconds read from a contract, or `None` when they are not a representable time.
+fn to_datetime(timestamp: U256) -> Option<DateTime<Utc>> {
+    let secs = i64::try_from(u64::try_from(timestamp).ok()?).ok()?;
+    Utc.timestamp_opt(secs, 0).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+
+    fn at(day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 5, day, hour, min, 0).unwrap()
+    }
+
+    fn pending(expires_at: DateTime<Utc>) -> Pending {
+        Pending {
+            request: ChainlinkRequest {
+                id: H256::zero(),
+                expiration: None,
+                block: 0,
+            },
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn schedules_hours_in_the_half_open_window() {
+        assert!(scheduled_between(&[3], at(1, 2, 59), at(1, 3, 0)));
+        assert!(!scheduled_between(&[3], at(1, 3, 0), at(1, 3, 1)));
+        assert!(!scheduled_between(&[3, 10], at(1, 4, 0), at(1, 9, 59)));
+    }
+
+    #[test]
+    fn schedules_hours_across_midnight() {
+        assert!(scheduled_between(&[0], at(1, 23, 59), at(2, 0, 1)));
+        assert!(scheduled_between(&[23], at(1, 23, 59), at(3, 0, 0)));
+        assert!(!scheduled_between(&[12], at(1, 23, 0), at(2, 11, 0)));
+    }
+
+    #[test]
+    fn schedules_nothing_for_an_empty_schedule_or_window() {
+        assert!(!scheduled_between(&[], at(1, 0, 0), at(3, 0, 0)));
+        assert!(!scheduled_between(&[3], at(1, 3, 0), at(1, 3, 0)));
+    }
+
+    #[test]
+    fn reports_the_pending_request() {
+        let mut state = State::default();
+        assert_eq!(state.pending_outcome(false, at(1, 3, 0)), None);
+
+        state.pending = Some(pending(at(1, 3, 5)));
+        assert_eq!(
+            state.pending_outcome(false, at(1, 3, 4)),
+            Some(PendingOutcome::Waiting)
+        );
+        assert_eq!(
+            state.pending_outcome(false, at(1, 3, 5)),
+            Some(PendingOutcome::Expired)
+        );
+        assert_eq!(
+            state.pending_outcome(true, at(1, 3, 5)),
+            Some(PendingOutcome::Fulfilled)
+        );
+    }
+
+    #[test]
+    fn owes_a_request_for_retries_and_scheduled_hours() {
+        let mut state = State::default();
+        assert_eq!(
+            state.reason(&[3], at(1, 2, 0), at(1, 3, 0)),
+            Some("schedule")
+        );
+        assert_eq!(state.reason(&[3], at(1, 3, 0), at(1, 4, 0)), None);
+
+        state.retry = true;
+        assert_eq!(state.reason(&[3], at(1, 3, 0), at(1, 4, 0)), Some("retry"));
+    }
+
+    #[test]
+    fn backs_off_after_failures() {
+        let poll = std::time::Duration::from_secs(60);
+        let mut state = State::default();
+
+        state.failed(at(1, 3, 0), poll);
+        assert!(state.retry);
+        assert_eq!(state.backoff_until, Some(at(1, 3, 1)));
+        assert!(state.is_backing_off(at(1, 3, 0)));
+        assert!(!state.is_backing_off(at(1, 3, 1)));
+
+        state.failed(at(1, 3, 1), poll);
+        assert_eq!(state.backoff_until, Some(at(1, 3, 3)));
+
+        for _ in 0..40 {
+            state.failed(at(1, 4, 0), poll);
+        }
+        assert_eq!(state.backoff_until, Some(at(1, 5, 0)));
+
+        state.submitted(pending(at(1, 4, 5)));
+        assert!(!state.retry);
+        assert_eq!(state.failures, 0);
+        assert!(!state.is_backing_off(at(1, 4, 0)));
+    }
+
+    #[test]
+    fn converts_only_representable_timestamps() {
+        assert_eq!(to_datetime(U256::from(1682910000u64)), Some(at(1, 3, 0)));
+        assert_eq!(to_datetime(U256::from(u64::MAX)), None);
+        assert_eq!(to_datetime(U256::MAX), None);
+    }
+}
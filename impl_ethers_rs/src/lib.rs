@@ -8,31 +8,50 @@ use prelude::*;
 use std::env;
 use std::str::FromStr;
 use std::sync::Arc;
+use telemetry::Metered;
 
+pub use ethers::types::Address;
+
+pub mod airdrop;
+pub mod approval;
+pub mod batch_mint;
+pub mod erc165;
+pub mod erc20;
+pub mod erc2981;
+pub mod erc721;
 pub mod event;
+pub mod fulfiller;
+pub mod job_spec;
+pub mod keeper;
+pub mod link_monitor;
 pub mod meta_transaction_wallet;
+pub mod multi_sig_wallet;
 pub mod nft_1155;
 pub mod nft_721;
 pub mod nft_market;
+pub mod oracle;
+pub mod progress;
 pub mod reveal_nft_721;
 pub mod sbt_721;
+pub mod snapshot;
+pub mod telemetry;
+
+pub type HttpProvider = Provider<Metered<Http>>;
+pub type WsProvider = Provider<Metered<Ws>>;
+pub type SignerClient = SignerMiddleware<HttpProvider, Wallet<k256::ecdsa::SigningKey>>;
+pub type NonceClient = NonceManagerMiddleware<SignerClient>;
 
-fn wei_to_ether(wei_amount: U256) -> f64 {
-    let ether_float = wei_amount.to_string().parse::<f64>().unwrap() * (10.0f64).powi(-18);
-    ether_float
+fn http_provider(network: Network) -> HttpProvider {
+    Provider::new(Metered::new(Http::from_str(&network.chain_url()).unwrap()))
 }
 
-fn ether_to_wei(ether_amount: f64) -> U256 {
-    let wei_float = ether_amount * (10.0f64).powi(18);
-    U256::from(wei_float.round() as u64)
+pub async fn ws_provider(url: &str) -> EthersResult<WsProvider> {
+    let ws = Ws::connect(url).await.map_err(ProviderError::from)?;
+    Ok(Provider::new(Metered::new(ws)))
 }
 
-fn query_contract(
-    contract_address: Address,
-    abi: Abi,
-    network: Network,
-) -> Contract<Provider<Http>> {
-    let provider = Arc::new(Provider::<Http>::try_from(network.chain_url()).unwrap());
+fn query_contract(contract_address: Address, abi: Abi, network: Network) -> Contract<HttpProvider> {
+    let provider = Arc::new(http_provider(network));
     Contract::new(contract_address, abi, provider)
 }
 
@@ -41,23 +60,40 @@ async fn transaction_contract(
     contract_address: Address,
     abi: Abi,
     network: Network,
-) -> Contract<SignerMiddleware<Provider<Http>, Wallet<k256::ecdsa::SigningKey>>> {
+) -> Contract<SignerClient> {
     let wallet = wallet_secret
         .parse::<LocalWallet>()
         .unwrap()
         .with_chain_id(network.chain_id());
 
-    let provider = Provider::<Http>::try_from(network.chain_url()).unwrap();
+    let provider = http_provider(network);
     let client = SignerMiddleware::new_with_provider_chain(provider, wallet)
         .await
         .unwrap();
     let client = Arc::new(client);
 
-    Contract::<SignerMiddleware<Provider<Http>, Wallet<k256::ecdsa::SigningKey>>>::new(
-        contract_address,
-        abi,
-        client.clone(),
-    )
+    Contract::<SignerClient>::new(contract_address, abi, client.clone())
+}
+
+async fn nonce_managed_contract(
+    wallet_secret: String,
+    contract_address: Address,
+    abi: Abi,
+    network: Network,
+) -> Contract<NonceClient> {
+    let wallet = wallet_secret
+        .parse::<LocalWallet>()
+        .unwrap()
+        .with_chain_id(network.chain_id());
+    let wallet_address = wallet.address();
+
+    let provider = http_provider(network);
+    let client = SignerMiddleware::new_with_provider_chain(provider, wallet)
+        .await
+        .unwrap();
+    let client = Arc::new(NonceManagerMiddleware::new(client, wallet_address));
+
+    Contract::<NonceClient>::new(contract_address, abi, client)
 }
 
 async fn deploy_contract(
@@ -65,13 +101,13 @@ async fn deploy_contract(
     abi: Abi,
     network: Network,
     bytecode: &str,
-) -> Contract<SignerMiddleware<Provider<Http>, Wallet<k256::ecdsa::SigningKey>>> {
+) -> Contract<SignerClient> {
     let wallet = wallet_secret
         .parse::<LocalWallet>()
         .unwrap()
         .with_chain_id(network.chain_id());
 
-    let provider = Provider::<Http>::try_from(network.chain_url()).unwrap();
+    let provider = http_provider(network);
     let client = SignerMiddleware::new_with_provider_chain(provider, wallet)
         .await
         .unwrap();
@@ -88,7 +124,7 @@ async fn deploy_contract(
         ..Default::default()
     });
     deployer
-        .confirmations(1 as usize)
+        .confirmations(1_usize)
         .legacy()
         .send()
         .await
@@ -98,7 +134,7 @@ async fn deploy_contract(
 pub async fn get_balance(network: Network) -> EthersResult<()> {
     let wallet_secret = env::var("WALLET_SECRET").expect("WALLET_SECRET must be set");
 
-    let provider = Provider::<Http>::try_from(network.chain_url()).unwrap();
+    let provider = http_provider(network);
     let wallet = wallet_secret
         .parse::<LocalWallet>()?
         .with_chain_id(network.chain_id());
@@ -110,18 +146,18 @@ pub async fn get_balance(network: Network) -> EthersResult<()> {
     let balance = client.get_balance(wallet.address(), None).await.unwrap();
 
     println!(
-        "balance: {:?}",
-        unit::to_ether(balance.to_string().as_str(), "wei")
+        "balance: {} ether",
+        unit::from_wei(balance, unit::Unit::Ether)
     );
 
     Ok(())
 }
 
-pub async fn send_eth(network: Network, eth: f64, to: String) -> EthersResult<()> {
+pub async fn send_eth(network: Network, wei: U256, to: String) -> EthersResult<()> {
     let to = to.to_owned().parse::<Address>().unwrap();
     let wallet_secret = env::var("WALLET_SECRET").expect("WALLET_SECRET must be set");
 
-    let provider = Provider::<Http>::try_from(network.chain_url()).unwrap();
+    let provider = http_provider(network);
     let wallet = wallet_secret
         .parse::<LocalWallet>()?
         .with_chain_id(network.chain_id());
@@ -130,10 +166,6 @@ pub async fn send_eth(network: Network, eth: f64, to: String) -> EthersResult<()
         .await
         .unwrap();
 
-    let wei = unit::to_wei(eth.to_string().as_str(), "ether");
-    let wei: u128 = wei.parse().unwrap();
-    let wei = U256::from(wei);
-
     let tx = TransactionRequest::new()
         .to(to)
         .value(wei)
@@ -153,10 +185,70 @@ pub fn to_address(from: String) -> Address {
     from.parse::<Address>().unwrap()
 }
 
+/// Wallet that signs a client's transactions.
+#[derive(Clone)]
+pub struct Account {
+    pub address: Address,
+    pub secret: String,
+}
+
+impl Account {
+    pub fn new(secret: String) -> EthersResult<Self> {
+        let wallet = secret.parse::<LocalWallet>()?;
+        Ok(Account {
+            address: wallet.address(),
+            secret,
+        })
+    }
+
+    /// The wallet configured by `WALLET_ADDRESS` and `WALLET_SECRET`.
+    pub fn from_env() -> Self {
+        let address = env::var("WALLET_ADDRESS").expect("WALLET_ADDRESS must be set");
+        let secret = env::var("WALLET_SECRET").expect("WALLET_SECRET must be set");
+
+        Account {
+            address: to_address(address),
+            secret,
+        }
+    }
+
+    /// Every wallet of ours: [`Account::from_env`], and `WALLET_SECRET_2` when it is set.
+    pub fn all_from_env() -> EthersResult<Vec<Self>> {
+        let mut accounts = vec![Account::from_env()];
+        if let Some(secret) = env::var("WALLET_SECRET_2").ok().filter(|v| !v.is_empty()) {
+            accounts.push(Account::new(secret)?);
+        }
+        Ok(accounts)
+    }
+}
+
+/// Parses `0x`-prefixed (or bare) hex calldata; an empty string yields empty bytes.
+pub fn to_bytes(from: &str) -> EthersResult<Bytes> {
+    Bytes::from_str(from.trim()).map_err(|_| Error::Internal(format!("invalid hex data: {}", from)))
+}
+
+/// Parses a token id given either in decimal or as `0x`-prefixed hex.
+pub fn to_token_id(from: &str) -> EthersResult<U256> {
+    let from = from.trim();
+    let res = match from.strip_prefix("0x").or_else(|| from.strip_prefix("0X")) {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(from).ok(),
+    };
+    res.ok_or_else(|| Error::Internal(format!("invalid token id: {}", from)))
+}
+
+/// Reads kept in flight at once while enumerating a collection.
+pub(crate) const ENUMERATION_CONCURRENCY: usize = 8;
+
+/// A count read from a contract, such as `totalSupply`, as something to iterate up to.
+pub(crate) fn to_count(count: U256) -> EthersResult<u64> {
+    u64::try_from(count).map_err(|_| Error::Internal(format!("count {} is out of range", count)))
+}
+
 pub async fn generate_keys() -> EthersResult<()> {
     let seckey =
         k256::elliptic_curve::SecretKey::<k256::Secp256k1>::random(&mut rand::thread_rng());
-    let seckey_str = ethers::utils::hex::encode(seckey.to_bytes().as_slice());
+    let seckey_str = ethers::utils::hex::encode(seckey.to_bytes());
     let pubkey = seckey.public_key();
     let pubkey_encoded = pubkey.to_encoded_point(false);
     let pubkey_str = ethers::utils::hex::encode(pubkey_encoded.as_bytes());
@@ -177,7 +269,7 @@ pub async fn sign(message: String) -> EthersResult<()> {
 
     let signature = wallet.sign_message(message).await?;
 
-    println!("signature: {}", signature.to_string());
+    println!("signature: {}", signature);
 
     Ok(())
 }
@@ -204,6 +296,13 @@ pub enum Error {
     Internal(String),
 }
 
+impl From<unit::Error> for Error {
+    fn from(e: unit::Error) -> Self {
+        let msg = format!("unit conversion error: {:?}", e);
+        Self::Internal(msg)
+    }
+}
+
 impl From<AbiError> for Error {
     fn from(e: AbiError) -> Self {
         let msg = format!("ethers contract abi error: {:?}", e);
@@ -211,8 +310,8 @@ impl From<AbiError> for Error {
     }
 }
 
-impl From<ContractError<Provider<Http>>> for Error {
-    fn from(e: ContractError<Provider<Http>>) -> Self {
+impl From<ContractError<HttpProvider>> for Error {
+    fn from(e: ContractError<HttpProvider>) -> Self {
         let msg = format!("ethers contract call error: {:?}", e);
         Self::Internal(msg)
     }
@@ -225,12 +324,8 @@ impl From<WalletError> for Error {
     }
 }
 
-impl From<ContractError<SignerMiddleware<Provider<Http>, Wallet<k256::ecdsa::SigningKey>>>>
-    for Error
-{
-    fn from(
-        e: ContractError<SignerMiddleware<Provider<Http>, Wallet<k256::ecdsa::SigningKey>>>,
-    ) -> Self {
+impl From<ContractError<SignerClient>> for Error {
+    fn from(e: ContractError<SignerClient>) -> Self {
         match e {
             ContractError::DecodingError(e) => {
                 let msg = format!("ethers contract sign error: {:?}", e);
@@ -245,22 +340,25 @@ impl From<ContractError<SignerMiddleware<Provider<Http>, Wallet<k256::ecdsa::Sig
                 Self::Internal(msg)
             }
             ContractError::ConstructorError => {
-                let msg =
-                    format!("ethers contract sign error: constructor is not defined in the ABI");
-                Self::Internal(msg)
+                let msg = "ethers contract sign error: constructor is not defined in the ABI";
+                Self::Internal(msg.to_string())
             }
             ContractError::ContractNotDeployed => {
-                let msg = format!("ethers contract sign error: Contract was not deployed");
-                Self::Internal(msg)
-            }
-            _ => {
-                let msg = format!("ethers contract sign error");
-                Self::Internal(msg)
+                let msg = "ethers contract sign error: Contract was not deployed";
+                Self::Internal(msg.to_string())
             }
+            _ => Self::Internal("ethers contract sign error".to_string()),
         }
     }
 }
 
+impl From<ContractError<NonceClient>> for Error {
+    fn from(e: ContractError<NonceClient>) -> Self {
+        let msg = format!("ethers contract call error: {:?}", e);
+        Self::Internal(msg)
+    }
+}
+
 impl From<ProviderError> for Error {
     fn from(e: ProviderError) -> Self {
         let msg = format!("ethers transaction error: {:?}", e);
@@ -275,9 +373,37 @@ impl From<abi::Error> for Error {
     }
 }
 
-impl From<ContractError<Provider<Ws>>> for Error {
-    fn from(e: ContractError<Provider<Ws>>) -> Self {
+impl From<ContractError<WsProvider>> for Error {
+    fn from(e: ContractError<WsProvider>) -> Self {
         let msg = format!("ethers ws error: {:?}", e);
         Self::Internal(msg)
     }
 }
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        let msg = format!("io error: {:?}", e);
+        Self::Internal(msg)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        let msg = format!("json parse error: {:?}", e);
+        Self::Internal(msg)
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        let msg = format!("csv error: {:?}", e);
+        Self::Internal(msg)
+    }
+}
+
+impl From<ipfs::Error> for Error {
+    fn from(e: ipfs::Error) -> Self {
+        let msg = format!("ipfs error: {:?}", e);
+        Self::Internal(msg)
+    }
+}
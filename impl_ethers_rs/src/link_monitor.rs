@@ -0,0 +1,141 @@
+use crate::erc20;
+use crate::erc721::Erc721;
+use crate::reveal_nft_721::client::Client;
+use crate::{Account, Error, EthersResult};
+use bigdecimal::BigDecimal;
+use ethers::prelude::*;
+use prelude::*;
+use std::env;
+
+/// Thresholds for the reveal contract's LINK, in LINK, read from the environment:
+///
+/// - `LINK_MIN_BALANCE`: warn when the contract holds less than this (defaults to enough
+///   for ten `updateTime` requests at the current fee).
+/// - `LINK_TOP_UP_AMOUNT`: what [`top_up`] sends from the owner wallet on a low balance.
+///
+/// Both are kept as written and parsed with the token's own decimals once they are known.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub min_balance: Option<String>,
+    pub top_up: Option<String>,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        let link = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+
+        Config {
+            min_balance: link("LINK_MIN_BALANCE"),
+            top_up: link("LINK_TOP_UP_AMOUNT"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LinkStatus {
+    pub token: Address,
+    pub decimals: u8,
+    pub balance: U256,
+    pub min_balance: U256,
+    pub fee: U256,
+    pub topped_up: Option<U256>,
+}
+
+impl LinkStatus {
+    pub fn is_low(&self) -> bool {
+        self.balance < self.min_balance
+    }
+
+    /// How many more `updateTime` requests the contract can pay for.
+    pub fn requests_left(&self) -> U256 {
+        if self.fee.is_zero() {
+            return U256::MAX;
+        }
+        self.balance / self.fee
+    }
+
+    /// `amount` of the token in whole LINK.
+    pub fn format(&self, amount: U256) -> BigDecimal {
+        unit::format_units(amount, self.decimals as u32)
+    }
+}
+
+/// Reads the LINK held by the reveal contract. Never sends anything; see [`top_up`].
+#[tracing::instrument(skip(reveal, owner), err)]
+pub async fn check(reveal: &Client, owner: Account, config: &Config) -> EthersResult<LinkStatus> {
+    let chainlink = reveal.chainlink_config().await?;
+    let link = erc20::Client::new(reveal.network(), chainlink.token, owner);
+    let decimals = link.decimals().await?;
+
+    let status = LinkStatus {
+        token: chainlink.token,
+        decimals,
+        balance: link.balance_of(reveal.address()).await?,
+        min_balance: match &config.min_balance {
+            Some(v) => parse_link(v, decimals, "LINK_MIN_BALANCE")?,
+            None => chainlink.fee * 10,
+        },
+        fee: chainlink.fee,
+        topped_up: None,
+    };
+    metrics::gauge!(
+        "reveal_link_balance",
+        status
+            .format(status.balance)
+            .to_string()
+            .parse::<f64>()
+            .unwrap_or_default()
+    );
+    if status.is_low() {
+        tracing::warn!(
+            balance = %status.format(status.balance),
+            min_balance = %status.format(status.min_balance),
+            "reveal contract is low on LINK"
+        );
+    }
+
+    Ok(status)
+}
+
+/// Sends `LINK_TOP_UP_AMOUNT` from `owner` to the reveal contract when `status` is below the
+/// threshold. Does nothing when no amount is configured or the owner cannot cover it.
+#[tracing::instrument(skip(reveal, owner, status), err)]
+pub async fn top_up(
+    reveal: &Client,
+    owner: Account,
+    config: &Config,
+    status: &mut LinkStatus,
+) -> EthersResult<()> {
+    if !status.is_low() {
+        return Ok(());
+    }
+    let amount = match &config.top_up {
+        Some(v) => parse_link(v, status.decimals, "LINK_TOP_UP_AMOUNT")?,
+        None => return Ok(()),
+    };
+    if amount.is_zero() {
+        return Ok(());
+    }
+
+    let link = erc20::Client::new(reveal.network(), status.token, owner);
+    let available = link.balance_of(link.wallet_address()).await?;
+    if available < amount {
+        tracing::warn!(
+            available = %status.format(available),
+            "owner wallet cannot cover the LINK top-up"
+        );
+        return Ok(());
+    }
+
+    link.transfer(reveal.address(), amount).await?;
+    metrics::increment_counter!("reveal_link_top_ups_total");
+    status.balance += amount;
+    status.topped_up = Some(amount);
+
+    Ok(())
+}
+
+fn parse_link(value: &str, decimals: u8, name: &str) -> EthersResult<U256> {
+    unit::parse_units(value, decimals as u32)
+        .map_err(|e| Error::Internal(format!("{} must be an amount of LINK: {:?}", name, e)))
+}
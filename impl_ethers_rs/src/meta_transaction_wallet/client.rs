@@ -1,4 +1,5 @@
-use crate::{query_contract, Error, EthersResult};
+use crate::erc721::{Erc721, Erc721Enumerable, Erc721Metadata, Erc721Transferable};
+use crate::{http_provider, query_contract, Account, Error, EthersResult};
 use ethers::abi::{ethabi, Abi};
 use ethers::prelude::transaction::eip712::{EIP712Domain, Eip712DomainType, TypedData, Types};
 use ethers::prelude::*;
@@ -22,42 +23,60 @@ pub struct Client {
 
 impl Client {
     pub fn new(network: Network) -> Self {
-        let user_wallet_address = env::var("WALLET_ADDRESS").expect("WALLET_ADDRESS must be set");
-        let user_wallet_secret = env::var("WALLET_SECRET").expect("WALLET_SECRET must be set");
         let relayer_wallet_secret = env::var("RELAYER_SECRET").expect("RELAYER_SECRET must be set");
 
-        Client {
-            user_wallet_address: user_wallet_address.parse::<Address>().unwrap(),
-            user_wallet_secret,
-            relayer_wallet_secret,
-            mtw_address: network
+        Self::with_address(
+            network,
+            network
                 .meta_transaction_wallet_address()
                 .parse::<Address>()
                 .unwrap(),
-            nft_address: network
+            network
                 .meta_transactional_nft_721_address()
                 .parse::<Address>()
                 .unwrap(),
+            Account::from_env(),
+            Account::new(relayer_wallet_secret).expect("RELAYER_SECRET must be a valid key"),
+        )
+    }
+
+    /// Binds to the wallet at `mtw_address` and the collection at `nft_address`. `user` signs
+    /// the meta transactions that `relayer` submits and pays for.
+    pub fn with_address(
+        network: Network,
+        mtw_address: Address,
+        nft_address: Address,
+        user: Account,
+        relayer: Account,
+    ) -> Self {
+        Client {
+            user_wallet_address: user.address,
+            user_wallet_secret: user.secret,
+            relayer_wallet_secret: relayer.secret,
+            mtw_address,
+            nft_address,
             mtw_abi: serde_json::from_str(include_str!("mtw_abi.json").trim()).unwrap(),
             nft_abi: serde_json::from_str(include_str!("nft_abi.json").trim()).unwrap(),
             network,
         }
     }
 
+    #[tracing::instrument(skip(self), err)]
     pub async fn get_nonce(&self) -> EthersResult<U256> {
         let res = query_contract(
             self.mtw_address.to_owned(),
             self.mtw_abi.to_owned(),
             self.network.to_owned(),
         )
-        .method::<_, U256>("getNonce", self.user_wallet_address.clone())?
+        .method::<_, U256>("getNonce", self.user_wallet_address)?
         .call()
         .await?;
         Ok(res)
     }
 
+    #[tracing::instrument(skip(self), err)]
     pub async fn mint(&self, to: Address, hash: String) -> EthersResult<()> {
-        let provider = Provider::<Http>::try_from(self.network.chain_url().clone()).unwrap();
+        let provider = http_provider(self.network);
 
         let user_wallet = self
             .user_wallet_secret
@@ -75,10 +94,7 @@ impl Client {
 
         let nft_function = self.nft_abi.function("mint").unwrap().clone();
         let encoded_nft_function = nft_function
-            .encode_input(&[
-                ethabi::Token::Address(to.into()),
-                ethabi::Token::String(hash),
-            ])
+            .encode_input(&[ethabi::Token::Address(to), ethabi::Token::String(hash)])
             .unwrap();
         let data = hex::encode(encoded_nft_function);
 
@@ -138,11 +154,11 @@ impl Client {
         let mut message: BTreeMap<String, serde_json::Value> = BTreeMap::new();
         message.insert(
             "from".to_string(),
-            serde_json::Value::String(format!("{:?}", self.user_wallet_address.clone())),
+            serde_json::Value::String(format!("{:?}", self.user_wallet_address)),
         );
         message.insert(
             "to".to_string(),
-            serde_json::Value::String(format!("{:?}", self.nft_address.clone())),
+            serde_json::Value::String(format!("{:?}", self.nft_address)),
         );
         message.insert(
             "value".to_string(),
@@ -162,7 +178,7 @@ impl Client {
             domain: EIP712Domain {
                 name: Some("MetaTransactionWallet".to_string()),
                 version: Some("0.0.1".to_string()),
-                chain_id: Some(U256::from(self.network.chain_id().clone())),
+                chain_id: Some(U256::from(self.network.chain_id())),
                 verifying_contract: Some(self.mtw_address),
                 salt: None,
             },
@@ -223,12 +239,40 @@ impl Client {
 
         let receipt = pending_tx.await?.unwrap();
         client
-            .get_transaction(receipt.transaction_hash.clone())
+            .get_transaction(receipt.transaction_hash)
             .await
             .map_err(|_e| Error::Internal("error".to_string()))?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
 }
+
+impl Erc721 for Client {
+    fn network(&self) -> Network {
+        self.network
+    }
+
+    fn address(&self) -> Address {
+        self.nft_address
+    }
+
+    fn abi(&self) -> &Abi {
+        &self.nft_abi
+    }
+
+    fn wallet_address(&self) -> Address {
+        self.user_wallet_address
+    }
+
+    fn wallet_secret(&self) -> String {
+        self.user_wallet_secret.to_owned()
+    }
+}
+
+impl Erc721Transferable for Client {}
+
+impl Erc721Metadata for Client {}
+
+impl Erc721Enumerable for Client {}
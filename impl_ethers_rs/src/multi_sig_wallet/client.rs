@@ -0,0 +1,406 @@
+use crate::event::{
+    ConfirmationEvent, ExecutionEvent, ExecutionFailureEvent, OwnerAddedEvent, OwnerRemovedEvent,
+    RequirementChangedEvent, SubmissionEvent,
+};
+use crate::multi_sig_wallet::proposal::{decode_call, PendingTransaction, Proposal};
+use crate::{
+    query_contract, to_count, transaction_contract, Account, Error, EthersResult, HttpProvider,
+    SignerClient, ENUMERATION_CONCURRENCY,
+};
+use ethers::abi::{Abi, Tokenize};
+use ethers::contract::{parse_log, Contract};
+use ethers::prelude::*;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use prelude::*;
+
+#[derive(Clone, Debug)]
+pub struct Client {
+    wallet_address: Address,
+    wallet_secret: String,
+    pub address: Address,
+    pub abi: Abi,
+    network: Network,
+}
+
+impl Client {
+    pub fn new(network: Network) -> Self {
+        Self::with_address(
+            network,
+            network
+                .multi_sig_wallet_address()
+                .parse::<Address>()
+                .unwrap(),
+            Account::from_env(),
+        )
+    }
+
+    /// Binds to the wallet at `address`, signing as the owner `account`.
+    pub fn with_address(network: Network, address: Address, account: Account) -> Self {
+        Client {
+            wallet_address: account.address,
+            wallet_secret: account.secret,
+            address,
+            abi: serde_json::from_str(include_str!("abi.json").trim()).unwrap(),
+            network,
+        }
+    }
+
+    fn query(&self) -> Contract<HttpProvider> {
+        query_contract(self.address, self.abi.to_owned(), self.network)
+    }
+
+    async fn transaction(&self) -> Contract<SignerClient> {
+        transaction_contract(
+            self.wallet_secret.clone(),
+            self.address,
+            self.abi.to_owned(),
+            self.network,
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn get_owners(&self) -> EthersResult<Vec<Address>> {
+        let res = self
+            .query()
+            .method::<_, Vec<Address>>("getOwners", ())?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn get_required(&self) -> EthersResult<U256> {
+        let res = self
+            .query()
+            .method::<_, U256>("getRequired", ())?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn transaction_count(&self) -> EthersResult<U256> {
+        let res = self
+            .query()
+            .method::<_, U256>("transactionCount", ())?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn get_transaction(&self, transaction_id: U256) -> EthersResult<MultiSigTransaction> {
+        let (destination, value, data, executed) = self
+            .query()
+            .method::<_, (Address, U256, Bytes, bool)>("getTransaction", transaction_id)?
+            .call()
+            .await?;
+        Ok(MultiSigTransaction {
+            id: transaction_id,
+            destination,
+            value,
+            data,
+            executed,
+        })
+    }
+
+    /// Current owners that have confirmed `transaction_id`.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn confirmations(&self, transaction_id: U256) -> EthersResult<Vec<Address>> {
+        let query = &self.query();
+        let owners = self.get_owners().await?;
+        let confirmed: Vec<bool> = stream::iter(&owners)
+            .map(|owner| async move {
+                let confirmed = query
+                    .method::<_, bool>("confirmations", (transaction_id, *owner))?
+                    .call()
+                    .await?;
+                EthersResult::Ok(confirmed)
+            })
+            .buffered(ENUMERATION_CONCURRENCY)
+            .try_collect()
+            .await?;
+        Ok(owners
+            .into_iter()
+            .zip(confirmed)
+            .filter_map(|(owner, confirmed)| confirmed.then_some(owner))
+            .collect())
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn add_owner(&self, owner: Address) -> EthersResult<Vec<MultiSigEvent>> {
+        self.send("addOwner", owner).await
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn remove_owner(&self, owner: Address) -> EthersResult<Vec<MultiSigEvent>> {
+        self.send("removeOwner", owner).await
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn change_requirement(&self, required: U256) -> EthersResult<Vec<MultiSigEvent>> {
+        self.send("changeRequirement", required).await
+    }
+
+    /// Submits a call of `data` with `value` wei to `destination` and confirms it as our
+    /// wallet; it executes right away when one confirmation is enough.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn submit_transaction(
+        &self,
+        destination: Address,
+        value: U256,
+        data: Bytes,
+    ) -> EthersResult<Vec<MultiSigEvent>> {
+        self.send("submitTransaction", (destination, value, data))
+            .await
+    }
+
+    /// Submits `proposal`; see [`Client::submit_transaction`].
+    #[tracing::instrument(skip(self), err)]
+    pub async fn propose(&self, proposal: Proposal) -> EthersResult<Vec<MultiSigEvent>> {
+        self.submit_transaction(proposal.destination, proposal.value, proposal.data)
+            .await
+    }
+
+    /// Every transaction not executed yet, oldest first, with its calldata decoded against
+    /// the bundled ABIs.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn pending_transactions(&self) -> EthersResult<Vec<PendingTransaction>> {
+        let transaction_count = to_count(self.transaction_count().await?)?;
+        let pending: Vec<Option<PendingTransaction>> = stream::iter(0..transaction_count)
+            .map(|transaction_id| async move {
+                let transaction = self.get_transaction(transaction_id.into()).await?;
+                if transaction.executed {
+                    return EthersResult::Ok(None);
+                }
+                let confirmations = self.confirmations(transaction.id).await?;
+                let call = decode_call(&transaction.data);
+                Ok(Some(PendingTransaction {
+                    transaction,
+                    confirmations,
+                    call,
+                }))
+            })
+            .buffered(ENUMERATION_CONCURRENCY)
+            .try_collect()
+            .await?;
+        Ok(pending.into_iter().flatten().collect())
+    }
+
+    /// Confirms `transaction_id` as our wallet, executing it once enough owners have.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn confirm_transaction(
+        &self,
+        transaction_id: U256,
+    ) -> EthersResult<Vec<MultiSigEvent>> {
+        self.send("confirmTransaction", transaction_id).await
+    }
+
+    /// Sends `name` and decodes the wallet events it emitted. Fails when the transaction was
+    /// dropped or reverted.
+    async fn send<T: Tokenize + Send>(
+        &self,
+        name: &str,
+        args: T,
+    ) -> EthersResult<Vec<MultiSigEvent>> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, ()>(name, args)?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let tx_hash = *tx;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        let receipt = receipt
+            .ok_or_else(|| Error::Internal(format!("{} {:?} was dropped", name, tx_hash)))?;
+        if receipt.status == Some(0.into()) {
+            return Err(Error::Internal(format!("{} {:?} reverted", name, tx_hash)));
+        }
+        Ok(decode_events(self.address, &receipt.logs))
+    }
+
+    pub fn wallet_address(&self) -> Address {
+        self.wallet_address
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MultiSigTransaction {
+    pub id: U256,
+    pub destination: Address,
+    pub value: U256,
+    pub data: Bytes,
+    pub executed: bool,
+}
+
+/// Transaction lifecycle and owner management events emitted by the wallet.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MultiSigEvent {
+    Submission {
+        transaction_id: U256,
+    },
+    Confirmation {
+        sender: Address,
+        transaction_id: U256,
+    },
+    Execution {
+        transaction_id: U256,
+    },
+    ExecutionFailure {
+        transaction_id: U256,
+    },
+    OwnerAdded {
+        owner: Address,
+    },
+    OwnerRemoved {
+        owner: Address,
+    },
+    RequirementChanged {
+        required: U256,
+    },
+}
+
+/// Decodes every event `wallet` emitted among `logs`, skipping logs of other contracts
+/// (such as a target the wallet executed a call on) and unrelated events.
+pub fn decode_events(wallet: Address, logs: &[Log]) -> Vec<MultiSigEvent> {
+    logs.iter()
+        .filter(|log| log.address == wallet)
+        .filter_map(|log| {
+            if let Ok(event) = parse_log::<SubmissionEvent>(log.clone()) {
+                return Some(MultiSigEvent::Submission {
+                    transaction_id: event.transaction_id,
+                });
+            }
+            if let Ok(event) = parse_log::<ConfirmationEvent>(log.clone()) {
+                return Some(MultiSigEvent::Confirmation {
+                    sender: event.sender,
+                    transaction_id: event.transaction_id,
+                });
+            }
+            if let Ok(event) = parse_log::<ExecutionEvent>(log.clone()) {
+                return Some(MultiSigEvent::Execution {
+                    transaction_id: event.transaction_id,
+                });
+            }
+            if let Ok(event) = parse_log::<ExecutionFailureEvent>(log.clone()) {
+                return Some(MultiSigEvent::ExecutionFailure {
+                    transaction_id: event.transaction_id,
+                });
+            }
+            if let Ok(event) = parse_log::<OwnerAddedEvent>(log.clone()) {
+                return Some(MultiSigEvent::OwnerAdded { owner: event.owner });
+            }
+            if let Ok(event) = parse_log::<OwnerRemovedEvent>(log.clone()) {
+                return Some(MultiSigEvent::OwnerRemoved { owner: event.owner });
+            }
+            if let Ok(event) = parse_log::<RequirementChangedEvent>(log.clone()) {
+                return Some(MultiSigEvent::RequirementChanged {
+                    required: event.required,
+                });
+            }
+            None
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallet() -> Address {
+        Address::from_low_u64_be(1)
+    }
+
+    fn log(address: Address, topics: Vec<H256>, data: Vec<u8>) -> Log {
+        Log {
+            address,
+            topics,
+            data: data.into(),
+            ..Default::default()
+        }
+    }
+
+    fn id(value: u64) -> H256 {
+        H256::from_low_u64_be(value)
+    }
+
+    #[test]
+    fn decodes_lifecycle_events() {
+        let sender = Address::from_low_u64_be(2);
+        let logs = vec![
+            log(wallet(), vec![SubmissionEvent::signature(), id(3)], vec![]),
+            log(
+                wallet(),
+                vec![ConfirmationEvent::signature(), sender.into(), id(3)],
+                vec![],
+            ),
+            log(wallet(), vec![ExecutionEvent::signature(), id(3)], vec![]),
+        ];
+        assert_eq!(
+            decode_events(wallet(), &logs),
+            vec![
+                MultiSigEvent::Submission {
+                    transaction_id: 3.into()
+                },
+                MultiSigEvent::Confirmation {
+                    sender,
+                    transaction_id: 3.into()
+                },
+                MultiSigEvent::Execution {
+                    transaction_id: 3.into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_owner_management_events() {
+        let owner = Address::from_low_u64_be(2);
+        let logs = vec![
+            log(
+                wallet(),
+                vec![OwnerAddedEvent::signature(), owner.into()],
+                vec![],
+            ),
+            log(
+                wallet(),
+                vec![OwnerRemovedEvent::signature(), owner.into()],
+                vec![],
+            ),
+            log(
+                wallet(),
+                vec![RequirementChangedEvent::signature()],
+                id(2).as_bytes().to_vec(),
+            ),
+        ];
+        assert_eq!(
+            decode_events(wallet(), &logs),
+            vec![
+                MultiSigEvent::OwnerAdded { owner },
+                MultiSigEvent::OwnerRemoved { owner },
+                MultiSigEvent::RequirementChanged { required: 2.into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_logs_of_other_contracts() {
+        // A target contract emitting an event with the same signature during execution.
+        let target = Address::from_low_u64_be(9);
+        let logs = vec![
+            log(target, vec![ExecutionEvent::signature(), id(7)], vec![]),
+            log(wallet(), vec![ExecutionEvent::signature(), id(3)], vec![]),
+        ];
+        assert_eq!(
+            decode_events(wallet(), &logs),
+            vec![MultiSigEvent::Execution {
+                transaction_id: 3.into()
+            }]
+        );
+    }
+}
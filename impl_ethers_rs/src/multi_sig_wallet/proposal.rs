@@ -0,0 +1,232 @@
+use crate::multi_sig_wallet::client::MultiSigTransaction;
+use crate::{Error, EthersResult};
+use ethers::abi::{Abi, Token, Tokenize};
+use ethers::prelude::*;
+use std::fmt;
+
+/// A contract call for the wallet to make once enough owners confirm it, in place of a
+/// transaction signed directly by one key.
+#[derive(Clone, Debug)]
+pub struct Proposal {
+    pub destination: Address,
+    pub value: U256,
+    pub data: Bytes,
+}
+
+impl Proposal {
+    /// Encodes `function(args)` of the contract at `destination` described by `abi`, i.e. the
+    /// calldata a client would otherwise send itself. An overloaded `function` is resolved by
+    /// the number and types of `args`, and fails when they fit more than one overload.
+    pub fn call<T: Tokenize>(
+        destination: Address,
+        abi: &Abi,
+        function: &str,
+        args: T,
+    ) -> EthersResult<Self> {
+        let tokens = args.into_tokens();
+        let overloads = abi
+            .functions_by_name(function)?
+            .iter()
+            .filter(|f| {
+                f.inputs.len() == tokens.len()
+                    && f.inputs
+                        .iter()
+                        .zip(&tokens)
+                        .all(|(param, token)| token.type_check(&param.kind))
+            })
+            .collect::<Vec<_>>();
+        let data = match overloads.as_slice() {
+            [f] => f.encode_input(&tokens)?,
+            [] => {
+                return Err(Error::Internal(format!(
+                    "no {} takes these arguments",
+                    function
+                )))
+            }
+            _ => {
+                return Err(Error::Internal(format!(
+                    "ambiguous call, arguments fit {}",
+                    overloads
+                        .iter()
+                        .map(|f| f.signature())
+                        .collect::<Vec<_>>()
+                        .join(" and ")
+                )))
+            }
+        };
+        Ok(Proposal {
+            destination,
+            value: U256::zero(),
+            data: Bytes::from(data),
+        })
+    }
+}
+
+/// A submitted transaction that has not executed yet, with who confirmed it so far.
+#[derive(Clone, Debug)]
+pub struct PendingTransaction {
+    pub transaction: MultiSigTransaction,
+    pub confirmations: Vec<Address>,
+    pub call: Option<DecodedCall>,
+}
+
+/// Calldata decoded against the ABIs bundled with this crate. `contracts` lists every
+/// bundled contract that has a function with this selector.
+#[derive(Clone, Debug)]
+pub struct DecodedCall {
+    pub contracts: Vec<&'static str>,
+    pub function: String,
+    pub args: Vec<String>,
+}
+
+impl fmt::Display for DecodedCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}({}) [{}]",
+            self.function,
+            self.args.join(", "),
+            self.contracts.join(", ")
+        )
+    }
+}
+
+fn bundled_abis() -> Vec<(&'static str, Abi)> {
+    let parse = |json: &str| serde_json::from_str::<Abi>(json.trim()).unwrap();
+    vec![
+        ("Nft721", parse(include_str!("../nft_721/abi.json"))),
+        ("Nft1155", parse(include_str!("../nft_1155/abi.json"))),
+        ("Sbt721", parse(include_str!("../sbt_721/abi.json"))),
+        (
+            "RevealNft721",
+            parse(include_str!("../reveal_nft_721/abi.json")),
+        ),
+        ("NftMarket", parse(include_str!("../nft_market/abi.json"))),
+        (
+            "MetaTransactionalNft721",
+            parse(include_str!("../meta_transaction_wallet/nft_abi.json")),
+        ),
+        ("MultiSigWallet", parse(include_str!("abi.json"))),
+    ]
+}
+
+/// Decodes `data` as a call to one of the bundled contracts, or `None` for plain transfers
+/// and calldata none of them understands.
+pub fn decode_call(data: &[u8]) -> Option<DecodedCall> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, input) = data.split_at(4);
+
+    let mut decoded: Option<DecodedCall> = None;
+    for (name, abi) in bundled_abis() {
+        let function = match abi
+            .functions()
+            .find(|function| function.short_signature() == selector)
+        {
+            Some(function) => function,
+            None => continue,
+        };
+        match decoded.as_mut() {
+            Some(call) => call.contracts.push(name),
+            None => {
+                if let Ok(tokens) = function.decode_input(input) {
+                    decoded = Some(DecodedCall {
+                        contracts: vec![name],
+                        function: function.name.clone(),
+                        args: tokens.iter().map(format_token).collect(),
+                    });
+                }
+            }
+        }
+    }
+    decoded
+}
+
+fn format_token(token: &Token) -> String {
+    match token {
+        Token::Address(address) => format!("{:?}", address),
+        Token::Uint(v) => v.to_string(),
+        Token::Int(v) => I256::from_raw(*v).to_string(),
+        Token::Bool(v) => v.to_string(),
+        Token::String(v) => format!("{:?}", v),
+        Token::FixedBytes(v) | Token::Bytes(v) => format!("0x{}", ethers::utils::hex::encode(v)),
+        Token::Array(tokens) | Token::FixedArray(tokens) => format!(
+            "[{}]",
+            tokens
+                .iter()
+                .map(format_token)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Token::Tuple(tokens) => format!(
+            "({})",
+            tokens
+                .iter()
+                .map(format_token)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{parse_abi, Function};
+
+    fn abi() -> Abi {
+        parse_abi(&[
+            "function mint(address to)",
+            "function mint(address to, uint256 amount)",
+            "function set(uint256 value)",
+            "function set(address value)",
+            "function limit(uint256 value)",
+            "function limit(uint128 value)",
+        ])
+        .unwrap()
+    }
+
+    fn selector(abi: &Abi, signature: &str) -> [u8; 4] {
+        abi.functions()
+            .find(|f| f.signature() == signature)
+            .map(Function::short_signature)
+            .unwrap()
+    }
+
+    fn call<T: Tokenize>(function: &str, args: T) -> EthersResult<[u8; 4]> {
+        let proposal = Proposal::call(Address::zero(), &abi(), function, args)?;
+        Ok(proposal.data[..4].try_into().unwrap())
+    }
+
+    #[test]
+    fn picks_the_overload_by_argument_count() {
+        let abi = abi();
+        let to = Address::from_low_u64_be(1);
+        assert_eq!(call("mint", to).unwrap(), selector(&abi, "mint(address)"));
+        assert_eq!(
+            call("mint", (to, U256::from(2))).unwrap(),
+            selector(&abi, "mint(address,uint256)")
+        );
+    }
+
+    #[test]
+    fn picks_the_overload_by_argument_type() {
+        let abi = abi();
+        assert_eq!(
+            call("set", U256::from(1)).unwrap(),
+            selector(&abi, "set(uint256)")
+        );
+        assert_eq!(
+            call("set", Address::from_low_u64_be(1)).unwrap(),
+            selector(&abi, "set(address)")
+        );
+    }
+
+    #[test]
+    fn rejects_ambiguous_and_unknown_calls() {
+        assert!(call("limit", U256::from(1)).is_err());
+        assert!(call("mint", ()).is_err());
+        assert!(call("burn", U256::from(1)).is_err());
+    }
+}
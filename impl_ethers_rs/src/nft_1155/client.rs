@@ -1,9 +1,27 @@
-use crate::{deploy_contract, query_contract, transaction_contract, EthersResult};
-use ethers::abi::Abi;
+use crate::airdrop::{self, AirdropResult, AirdropRow};
+use crate::approval;
+use crate::batch_mint::{self, MintResult, MintRow};
+use crate::multi_sig_wallet::proposal::Proposal;
+use crate::{
+    deploy_contract, nonce_managed_contract, query_contract, to_count, transaction_contract,
+    Account, Error, EthersResult, HttpProvider, ENUMERATION_CONCURRENCY,
+};
+use ethers::abi::{Abi, Tokenize};
+use ethers::contract::Contract;
 use ethers::prelude::*;
+use futures::stream::{self, StreamExt, TryStreamExt};
 use prelude::*;
-use std::env;
-use std::str::FromStr;
+use std::path::Path;
+
+/// Substitutes the `{id}` placeholder of a `uri(id)` result with the lowercase, 64 digit
+/// hex token id, as the ERC-1155 metadata spec describes.
+pub fn expand_uri(uri: &str, token_id: U256) -> String {
+    uri.replace("{id}", &format!("{:064x}", token_id))
+}
+
+/// Token ids read per `balanceOfBatch` call, keeping each call within RPC gas and response
+/// limits as the collection grows.
+pub const BALANCE_BATCH_SIZE: usize = 500;
 
 #[derive(Clone, Debug)]
 pub struct Client {
@@ -16,18 +34,30 @@ pub struct Client {
 
 impl Client {
     pub fn new(network: Network) -> Self {
-        let wallet_address = env::var("WALLET_ADDRESS").expect("WALLET_ADDRESS must be set");
-        let wallet_secret = env::var("WALLET_SECRET").expect("WALLET_SECRET must be set");
+        Self::with_address(
+            network,
+            network.nft_1155_address().parse::<Address>().unwrap(),
+            Account::from_env(),
+        )
+    }
 
+    /// Binds to the collection at `address`, signing transactions with `account`.
+    pub fn with_address(network: Network, address: Address, account: Account) -> Self {
         Client {
-            wallet_address: wallet_address.parse::<Address>().unwrap(),
-            wallet_secret,
-            address: network.nft_1155_address().parse::<Address>().unwrap(),
+            wallet_address: account.address,
+            wallet_secret: account.secret,
+            address,
             abi: serde_json::from_str(include_str!("abi.json").trim()).unwrap(),
             network,
         }
     }
 
+    /// `function(args)` as a call for the MultiSigWallet to make, for a collection it owns.
+    pub fn proposal<T: Tokenize>(&self, function: &str, args: T) -> EthersResult<Proposal> {
+        Proposal::call(self.address, &self.abi, function, args)
+    }
+
+    #[tracing::instrument(skip(self), err)]
     pub async fn name(&self) -> EthersResult<String> {
         let res = query_contract(
             self.address.to_owned(),
@@ -40,42 +70,123 @@ impl Client {
         Ok(res)
     }
 
-    pub async fn latest_token_id(&self) -> EthersResult<u128> {
+    #[tracing::instrument(skip(self), err)]
+    pub async fn latest_token_id(&self) -> EthersResult<U256> {
         let res = query_contract(
             self.address.to_owned(),
             self.abi.to_owned(),
             self.network.to_owned(),
         )
-        .method::<_, u128>("latestTokenId", ())?
+        .method::<_, U256>("latestTokenId", ())?
         .call()
         .await?;
         Ok(res)
     }
 
-    pub async fn total_supply(&self) -> EthersResult<u128> {
+    #[tracing::instrument(skip(self), err)]
+    pub async fn total_supply(&self) -> EthersResult<U256> {
         let res = query_contract(
             self.address.to_owned(),
             self.abi.to_owned(),
             self.network.to_owned(),
         )
-        .method::<_, u128>("totalSupply", ())?
+        .method::<_, U256>("totalSupply", ())?
         .call()
         .await?;
         Ok(res)
     }
 
-    pub async fn total_owned(&self) -> EthersResult<u128> {
+    #[tracing::instrument(skip(self), err)]
+    pub async fn total_owned(&self) -> EthersResult<U256> {
         let res = query_contract(
             self.address.to_owned(),
             self.abi.to_owned(),
             self.network.to_owned(),
         )
-        .method::<_, u128>("totalOwned", ())?
+        .method::<_, U256>("totalOwned", ())?
         .call()
         .await?;
         Ok(res)
     }
 
+    #[tracing::instrument(skip(self), err)]
+    pub async fn balance_of(&self, owner: Address, token_id: U256) -> EthersResult<U256> {
+        let res = query_contract(
+            self.address.to_owned(),
+            self.abi.to_owned(),
+            self.network.to_owned(),
+        )
+        .method::<_, U256>("balanceOf", (owner, token_id))?
+        .call()
+        .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn balance_of_batch(
+        &self,
+        owners: Vec<Address>,
+        token_ids: Vec<U256>,
+    ) -> EthersResult<Vec<U256>> {
+        let res = query_contract(
+            self.address.to_owned(),
+            self.abi.to_owned(),
+            self.network.to_owned(),
+        )
+        .method::<_, Vec<U256>>("balanceOfBatch", (owners, token_ids))?
+        .call()
+        .await?;
+        Ok(res)
+    }
+
+    /// Returns the metadata URI of `token_id` with any `{id}` placeholder substituted
+    /// as described in the ERC-1155 metadata spec.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn uri(&self, token_id: U256) -> EthersResult<String> {
+        let res = query_contract(
+            self.address.to_owned(),
+            self.abi.to_owned(),
+            self.network.to_owned(),
+        )
+        .method::<_, String>("uri", token_id)?
+        .call()
+        .await?;
+        Ok(expand_uri(&res, token_id))
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn is_owner(&self, token_id: U256, target: Address) -> EthersResult<bool> {
+        let res = query_contract(
+            self.address.to_owned(),
+            self.abi.to_owned(),
+            self.network.to_owned(),
+        )
+        .method::<_, bool>("isOwner", (token_id, target))?
+        .call()
+        .await?;
+        Ok(res)
+    }
+
+    /// Lists every `(token_id, amount)` held by `owner` among the ids minted so far, reading
+    /// the balances in `balanceOfBatch` calls of at most [`BALANCE_BATCH_SIZE`] ids.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn holdings(&self, owner: Address) -> EthersResult<Vec<(U256, U256)>> {
+        let latest_token_id = to_count(self.latest_token_id().await?)?;
+        let token_ids = (1..=latest_token_id).map(U256::from).collect::<Vec<_>>();
+        let balances: Vec<Vec<U256>> = stream::iter(token_ids.chunks(BALANCE_BATCH_SIZE))
+            .map(|chunk| self.balance_of_batch(vec![owner; chunk.len()], chunk.to_vec()))
+            .buffered(ENUMERATION_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        Ok(token_ids
+            .into_iter()
+            .zip(balances.into_iter().flatten())
+            .filter(|(_, amount)| !amount.is_zero())
+            .collect())
+    }
+
+    #[tracing::instrument(skip(self), err)]
     pub async fn mint(&self, hash: String, amount: u128) -> EthersResult<()> {
         let call = transaction_contract(
             self.wallet_secret.to_owned(),
@@ -90,12 +201,65 @@ impl Client {
         let tx = call.send().await?;
         let receipt = tx.await?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
 
-    pub async fn transfer(&self, to: Address, token_id: u128) -> EthersResult<()> {
+    /// Mints every row of a manifest; see [`batch_mint::mint`].
+    #[tracing::instrument(skip(self, rows), err)]
+    pub async fn batch_mint(
+        &self,
+        rows: Vec<MintRow>,
+        progress_path: &Path,
+        concurrency: usize,
+    ) -> EthersResult<Vec<MintResult>> {
+        let contract = nonce_managed_contract(
+            self.wallet_secret.to_owned(),
+            self.address.to_owned(),
+            self.abi.to_owned(),
+            self.network.to_owned(),
+        )
+        .await;
+        batch_mint::mint(contract, Schema::ERC1155, rows, progress_path, concurrency).await
+    }
+
+    /// Sends tokens to every recipient row; see [`airdrop::airdrop`].
+    #[tracing::instrument(skip(self, rows), err)]
+    pub async fn airdrop(
+        &self,
+        rows: Vec<AirdropRow>,
+        progress_path: &Path,
+        rate: f64,
+        concurrency: usize,
+    ) -> EthersResult<Vec<AirdropResult>> {
+        let contract = nonce_managed_contract(
+            self.wallet_secret.to_owned(),
+            self.address.to_owned(),
+            self.abi.to_owned(),
+            self.network.to_owned(),
+        )
+        .await;
+        airdrop::airdrop(
+            contract,
+            Schema::ERC1155,
+            self.wallet_address,
+            rows,
+            progress_path,
+            rate,
+            concurrency,
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn transfer(
+        &self,
+        to: Address,
+        token_id: U256,
+        amount: U256,
+        data: Bytes,
+    ) -> EthersResult<()> {
         let call = transaction_contract(
             self.wallet_secret.to_owned(),
             self.address.to_owned(),
@@ -105,25 +269,32 @@ impl Client {
         .await
         .method::<_, H256>(
             "safeTransferFrom",
-            (
-                self.wallet_address,
-                to,
-                token_id,
-                1 as u64,
-                Bytes::from_str("").unwrap(),
-            ),
+            (self.wallet_address, to, token_id, amount, data),
         )?
         .gas(GAS_LIMIT)
         .gas_price(GAS_PRICE);
         let tx = call.send().await?;
         let receipt = tx.await?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
 
-    pub async fn set_approval_for_all(&self) -> EthersResult<()> {
+    #[tracing::instrument(skip(self), err)]
+    pub async fn batch_transfer(
+        &self,
+        to: Address,
+        token_ids: Vec<U256>,
+        amounts: Vec<U256>,
+        data: Bytes,
+    ) -> EthersResult<()> {
+        if token_ids.is_empty() || token_ids.len() != amounts.len() {
+            return Err(Error::Internal(
+                "token ids and amounts must be non-empty and of equal length".to_string(),
+            ));
+        }
+
         let call = transaction_contract(
             self.wallet_secret.to_owned(),
             self.address.to_owned(),
@@ -132,25 +303,91 @@ impl Client {
         )
         .await
         .method::<_, H256>(
-            "setApprovalForAll",
-            (
-                self.network
-                    .nft_market_address()
-                    .parse::<Address>()
-                    .unwrap(),
-                true,
-            ),
+            "safeBatchTransferFrom",
+            (self.wallet_address, to, token_ids, amounts, data),
         )?
         .gas(GAS_LIMIT)
         .gas_price(GAS_PRICE);
         let tx = call.send().await?;
         let receipt = tx.await?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
 
+    #[tracing::instrument(skip(self), err)]
+    pub async fn set_approval_for_all(
+        &self,
+        operator: Address,
+        approved: bool,
+    ) -> EthersResult<()> {
+        let call = transaction_contract(
+            self.wallet_secret.to_owned(),
+            self.address.to_owned(),
+            self.abi.to_owned(),
+            self.network.to_owned(),
+        )
+        .await
+        .method::<_, H256>("setApprovalForAll", (operator, approved))?
+        .gas(GAS_LIMIT)
+        .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn is_approved_for_all(
+        &self,
+        owner: Address,
+        operator: Address,
+    ) -> EthersResult<bool> {
+        let res = query_contract(
+            self.address.to_owned(),
+            self.abi.to_owned(),
+            self.network.to_owned(),
+        )
+        .method::<_, bool>("isApprovedForAll", (owner, operator))?
+        .call()
+        .await?;
+        Ok(res)
+    }
+
+    /// Operators currently approved for all of `owner`'s tokens; see
+    /// [`approval::approved_operators`].
+    #[tracing::instrument(skip(self), err)]
+    pub async fn approved_operators(
+        &self,
+        owner: Address,
+        from_block: u64,
+    ) -> EthersResult<Vec<Address>> {
+        approval::approved_operators(&self.query(), owner, from_block).await
+    }
+
+    /// Revokes every operator approval `accounts` still grant; see
+    /// [`approval::revoke_approvals`].
+    #[tracing::instrument(skip(self, accounts), err)]
+    pub async fn revoke_approvals(
+        &self,
+        accounts: &[Account],
+        from_block: u64,
+    ) -> EthersResult<Vec<(Address, Address)>> {
+        approval::revoke_approvals(&self.query(), self.network, accounts, from_block).await
+    }
+
+    fn query(&self) -> Contract<HttpProvider> {
+        query_contract(
+            self.address.to_owned(),
+            self.abi.to_owned(),
+            self.network.to_owned(),
+        )
+    }
+
+    #[tracing::instrument(skip(self), err)]
     pub async fn deploy(&self) -> EthersResult<()> {
         let contract = deploy_contract(
             self.wallet_secret.to_owned(),
@@ -1,8 +1,8 @@
-use crate::{deploy_contract, query_contract, transaction_contract, EthersResult};
+use crate::erc721::{Erc721, Erc721Enumerable, Erc721Metadata, Erc721Mintable, Erc721Transferable};
+use crate::{deploy_contract, Account, EthersResult};
 use ethers::abi::Abi;
 use ethers::prelude::*;
 use prelude::*;
-use std::env;
 
 #[derive(Clone, Debug)]
 pub struct Client {
@@ -15,143 +15,86 @@ pub struct Client {
 
 impl Client {
     pub fn new(network: Network) -> Self {
-        let wallet_address = env::var("WALLET_ADDRESS").expect("WALLET_ADDRESS must be set");
-        let wallet_secret = env::var("WALLET_SECRET").expect("WALLET_SECRET must be set");
+        Self::with_address(
+            network,
+            network.nft_721_address().parse::<Address>().unwrap(),
+            Account::from_env(),
+        )
+    }
 
+    /// Binds to the collection at `address`, signing transactions with `account`.
+    pub fn with_address(network: Network, address: Address, account: Account) -> Self {
         Client {
-            wallet_address: wallet_address.parse::<Address>().unwrap(),
-            wallet_secret,
-            address: network.nft_721_address().parse::<Address>().unwrap(),
+            wallet_address: account.address,
+            wallet_secret: account.secret,
+            address,
             abi: serde_json::from_str(include_str!("abi.json").trim()).unwrap(),
             network,
         }
     }
 
-    pub async fn name(&self) -> EthersResult<String> {
-        let res = query_contract(
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .method::<_, String>("name", ())?
-        .call()
-        .await?;
+    #[tracing::instrument(skip(self), err)]
+    pub async fn latest_token_id(&self) -> EthersResult<U256> {
+        let res = self
+            .query()
+            .method::<_, U256>("latestTokenId", ())?
+            .call()
+            .await?;
         Ok(res)
     }
 
-    pub async fn latest_token_id(&self) -> EthersResult<u128> {
-        let res = query_contract(
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .method::<_, u128>("latestTokenId", ())?
-        .call()
-        .await?;
+    #[tracing::instrument(skip(self), err)]
+    pub async fn total_owned(&self) -> EthersResult<U256> {
+        let res = self
+            .query()
+            .method::<_, U256>("totalOwned", ())?
+            .call()
+            .await?;
         Ok(res)
     }
 
-    pub async fn total_supply(&self) -> EthersResult<u128> {
-        let res = query_contract(
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .method::<_, u128>("totalSupply", ())?
-        .call()
-        .await?;
-        Ok(res)
-    }
-
-    pub async fn total_owned(&self) -> EthersResult<u128> {
-        let res = query_contract(
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .method::<_, u128>("totalOwned", ())?
-        .call()
-        .await?;
-        Ok(res)
-    }
-
-    pub async fn mint(&self, hash: String) -> EthersResult<()> {
-        let call = transaction_contract(
+    #[tracing::instrument(skip(self), err)]
+    pub async fn deploy(&self) -> EthersResult<()> {
+        let contract = deploy_contract(
             self.wallet_secret.to_owned(),
-            self.address.to_owned(),
             self.abi.to_owned(),
             self.network.to_owned(),
+            include_str!("bin").trim(),
         )
-        .await
-        .method::<_, H256>("mint", hash)?
-        .gas(GAS_LIMIT)
-        .gas_price(GAS_PRICE);
-        let tx = call.send().await?;
-        let receipt = tx.await?;
+        .await;
 
-        println!("{:?}", receipt);
+        println!("deployed to: {:?}", contract.address());
 
         Ok(())
     }
+}
 
-    pub async fn transfer(&self, to: Address, token_id: u128) -> EthersResult<()> {
-        let call = transaction_contract(
-            self.wallet_secret.to_owned(),
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .await
-        .method::<_, H256>("safeTransferFrom", (self.wallet_address, to, token_id))?
-        .gas(GAS_LIMIT)
-        .gas_price(GAS_PRICE);
-        let tx = call.send().await?;
-        let receipt = tx.await?;
+impl Erc721 for Client {
+    fn network(&self) -> Network {
+        self.network
+    }
 
-        println!("{:?}", receipt);
+    fn address(&self) -> Address {
+        self.address
+    }
 
-        Ok(())
+    fn abi(&self) -> &Abi {
+        &self.abi
     }
 
-    pub async fn set_approval_for_all(&self) -> EthersResult<()> {
-        let call = transaction_contract(
-            self.wallet_secret.to_owned(),
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .await
-        .method::<_, H256>(
-            "setApprovalForAll",
-            (
-                self.network
-                    .nft_market_address()
-                    .parse::<Address>()
-                    .unwrap(),
-                true,
-            ),
-        )?
-        .gas(GAS_LIMIT)
-        .gas_price(GAS_PRICE);
-        let tx = call.send().await?;
-        let receipt = tx.await?;
-
-        println!("{:?}", receipt);
+    fn wallet_address(&self) -> Address {
+        self.wallet_address
+    }
 
-        Ok(())
+    fn wallet_secret(&self) -> String {
+        self.wallet_secret.to_owned()
     }
+}
 
-    pub async fn deploy(&self) -> EthersResult<()> {
-        let contract = deploy_contract(
-            self.wallet_secret.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-            include_str!("bin").trim(),
-        )
-        .await;
+impl Erc721Transferable for Client {}
 
-        println!("deployed to: {:?}", contract.address());
+impl Erc721Metadata for Client {}
 
-        Ok(())
-    }
-}
+impl Erc721Enumerable for Client {}
+
+impl Erc721Mintable for Client {}
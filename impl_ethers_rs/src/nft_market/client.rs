@@ -1,8 +1,13 @@
-use crate::{ether_to_wei, query_contract, transaction_contract, wei_to_ether, EthersResult};
-use ethers::abi::Abi;
+use crate::erc165::{self, ERC2981};
+use crate::erc2981::{query_royalty_info, Royalty};
+use crate::multi_sig_wallet::proposal::Proposal;
+use crate::{query_contract, transaction_contract, Account, EthersResult};
+use bigdecimal::BigDecimal;
+use ethers::abi::{Abi, Tokenize};
 use ethers::prelude::*;
 use ethers::types::U256;
 use prelude::*;
+use std::collections::HashMap;
 use std::env;
 
 #[derive(Clone, Debug)]
@@ -18,14 +23,29 @@ impl Client {
         let wallet_secret =
             env::var("NFT_MARKET_OWNER_SECRET").expect("NFT_MARKET_OWNER_SECRET must be set");
 
+        Self::with_address(
+            network,
+            network.nft_market_address().parse::<Address>().unwrap(),
+            Account::new(wallet_secret).expect("NFT_MARKET_OWNER_SECRET must be a valid key"),
+        )
+    }
+
+    /// Binds to the market at `address`, signing admin transactions with `account`.
+    pub fn with_address(network: Network, address: Address, account: Account) -> Self {
         Client {
-            wallet_secret,
-            address: network.nft_market_address().parse::<Address>().unwrap(),
+            wallet_secret: account.secret,
+            address,
             abi: serde_json::from_str(include_str!("abi.json").trim()).unwrap(),
             network,
         }
     }
 
+    /// `function(args)` as a call for the MultiSigWallet to make, for a market it owns.
+    pub fn proposal<T: Tokenize>(&self, function: &str, args: T) -> EthersResult<Proposal> {
+        Proposal::call(self.address, &self.abi, function, args)
+    }
+
+    #[tracing::instrument(skip(self), err)]
     pub async fn get_sell_order_keys(&self) -> EthersResult<Vec<String>> {
         let res: Vec<String> = query_contract(
             self.address.to_owned(),
@@ -39,6 +59,7 @@ impl Client {
         Ok(res)
     }
 
+    #[tracing::instrument(skip(self), err)]
     pub async fn get_all_sell_order(&self) -> EthersResult<Vec<NFT>> {
         let res: Vec<(Address, U256, Address, U256, String)> = query_contract(
             self.address.to_owned(),
@@ -56,7 +77,7 @@ impl Client {
                     contract_address,
                     token_id,
                     seller,
-                    price: wei_to_ether(price),
+                    price,
                     token_uri,
                 },
             )
@@ -65,12 +86,43 @@ impl Client {
         Ok(items)
     }
 
+    /// Every sell order with the royalty its collection claims on the listed price, when
+    /// the collection supports ERC-2981.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn get_all_sell_order_with_royalty(&self) -> EthersResult<Vec<Listing>> {
+        let items = self.get_all_sell_order().await?;
+
+        let mut supports_royalty = HashMap::new();
+        let mut listings = vec![];
+        for nft in items {
+            let supported = match supports_royalty.get(&nft.contract_address) {
+                Some(supported) => *supported,
+                None => {
+                    let supported =
+                        erc165::supports(self.network, nft.contract_address, ERC2981).await?;
+                    supports_royalty.insert(nft.contract_address, supported);
+                    supported
+                }
+            };
+            let royalty = if supported {
+                query_royalty_info(self.network, nft.contract_address, nft.token_id, nft.price)
+                    .await?
+            } else {
+                None
+            };
+            listings.push(Listing { nft, royalty });
+        }
+
+        Ok(listings)
+    }
+
+    #[tracing::instrument(skip(self, seller_wallet_secret), err)]
     pub async fn sell_order(
         &self,
         seller_wallet_secret: String,
         contract_address: String,
-        token_id: u128,
-        ether: f64,
+        token_id: U256,
+        price: U256,
     ) -> EthersResult<()> {
         let call = transaction_contract(
             seller_wallet_secret,
@@ -84,7 +136,7 @@ impl Client {
             (
                 contract_address.parse::<Address>().unwrap(),
                 token_id,
-                ether_to_wei(ether),
+                price,
             ),
         )?
         .gas(GAS_LIMIT)
@@ -92,16 +144,17 @@ impl Client {
         let tx = call.send().await?;
         let receipt = tx.await?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
 
+    #[tracing::instrument(skip(self, seller_wallet_secret), err)]
     pub async fn cancel_order(
         &self,
         seller_wallet_secret: String,
         contract_address: String,
-        token_id: u128,
+        token_id: U256,
     ) -> EthersResult<()> {
         let call = transaction_contract(
             seller_wallet_secret,
@@ -119,15 +172,16 @@ impl Client {
         let tx = call.send().await?;
         let receipt = tx.await?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
 
+    #[tracing::instrument(skip(self), err)]
     pub async fn cancel_order_by_admin(
         &self,
         contract_address: String,
-        token_id: u128,
+        token_id: U256,
     ) -> EthersResult<()> {
         let call = transaction_contract(
             self.wallet_secret.to_owned(),
@@ -145,17 +199,18 @@ impl Client {
         let tx = call.send().await?;
         let receipt = tx.await?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
 
+    #[tracing::instrument(skip(self, buyer_wallet_secret), err)]
     pub async fn buy_order(
         &self,
         buyer_wallet_secret: String,
         contract_address: String,
-        token_id: u128,
-        ether: f64,
+        token_id: U256,
+        price: U256,
     ) -> EthersResult<()> {
         let call = transaction_contract(
             buyer_wallet_secret,
@@ -170,11 +225,11 @@ impl Client {
         )?
         .gas(GAS_LIMIT)
         .gas_price(GAS_PRICE)
-        .value(ether_to_wei(ether));
+        .value(price);
         let tx = call.send().await?;
         let receipt = tx.await?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
@@ -185,6 +240,18 @@ pub struct NFT {
     pub contract_address: Address,
     pub token_id: U256,
     pub seller: Address,
-    pub price: f64,
+    pub price: U256,
     pub token_uri: String,
 }
+
+impl NFT {
+    pub fn price_in(&self, unit: unit::Unit) -> BigDecimal {
+        unit::from_wei(self.price, unit)
+    }
+}
+
+#[derive(Debug)]
+pub struct Listing {
+    pub nft: NFT,
+    pub royalty: Option<Royalty>,
+}
@@ -0,0 +1,113 @@
+use crate::event::OracleRequestEvent;
+use crate::{http_provider, query_contract, transaction_contract, Account, EthersResult};
+use ethers::abi::{parse_abi, Abi};
+use ethers::prelude::*;
+use prelude::*;
+use std::env;
+
+/// Client for the Chainlink `Oracle` (Operator) contract, acting as its node account.
+#[derive(Clone, Debug)]
+pub struct Client {
+    pub wallet_address: Address,
+    wallet_secret: String,
+    pub address: Address,
+    abi: Abi,
+    network: Network,
+}
+
+impl Client {
+    /// Binds to the configured oracle, signing fulfillments with `CHAINLINK_NODE_SECRET`.
+    pub fn new(network: Network) -> Self {
+        let wallet_secret =
+            env::var("CHAINLINK_NODE_SECRET").expect("CHAINLINK_NODE_SECRET must be set");
+
+        Self::with_address(
+            network,
+            network.oracle_address().parse::<Address>().unwrap(),
+            Account::new(wallet_secret).expect("CHAINLINK_NODE_SECRET must be a valid key"),
+        )
+    }
+
+    pub fn with_address(network: Network, address: Address, account: Account) -> Self {
+        Client {
+            wallet_address: account.address,
+            wallet_secret: account.secret,
+            address,
+            abi: parse_abi(&[
+                "function isAuthorizedSender(address sender) external view returns (bool)",
+                "function fulfillOracleRequest2(bytes32 requestId, uint256 payment, address callbackAddress, bytes4 callbackFunctionId, uint256 expiration, bytes data) external returns (bool)",
+            ])
+            .unwrap(),
+            network,
+        }
+    }
+
+    /// Whether our node account may fulfill requests; see `setAuthorizedSenders`.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn is_authorized_sender(&self) -> EthersResult<bool> {
+        let res = query_contract(self.address, self.abi.to_owned(), self.network)
+            .method::<_, bool>("isAuthorizedSender", self.wallet_address)?
+            .call()
+            .await?;
+        Ok(res)
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn block_number(&self) -> EthersResult<u64> {
+        Ok(http_provider(self.network)
+            .get_block_number()
+            .await?
+            .as_u64())
+    }
+
+    /// `OracleRequest` logs emitted between `from_block` and `to_block` inclusive.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn oracle_requests(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> EthersResult<Vec<OracleRequestEvent>> {
+        let res = query_contract(self.address, self.abi.to_owned(), self.network)
+            .event::<OracleRequestEvent>()
+            .from_block(from_block)
+            .to_block(to_block)
+            .query()
+            .await?;
+        Ok(res)
+    }
+
+    /// Answers `request` with `data`, the ABI-encoded arguments of its callback.
+    #[tracing::instrument(skip(self, request, data), fields(request_id = ?H256::from(request.request_id)), err)]
+    pub async fn fulfill_oracle_request_2(
+        &self,
+        request: &OracleRequestEvent,
+        data: Bytes,
+    ) -> EthersResult<()> {
+        let call = transaction_contract(
+            self.wallet_secret.clone(),
+            self.address,
+            self.abi.to_owned(),
+            self.network,
+        )
+        .await
+        .method::<_, bool>(
+            "fulfillOracleRequest2",
+            (
+                request.request_id,
+                request.payment,
+                request.callback_addr,
+                request.callback_function_id,
+                request.cancel_expiration,
+                data,
+            ),
+        )?
+        .gas(GAS_LIMIT)
+        .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{EthersResult, NonceClient};
+use ethers::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Per-row results of a long-running job, persisted as JSON after every update so an
+/// interrupted run can resume without repeating rows that already went through.
+#[derive(Clone, Debug)]
+pub struct Progress<T> {
+    path: PathBuf,
+    rows: BTreeMap<usize, T>,
+}
+
+impl<T: Serialize + DeserializeOwned + Clone> Progress<T> {
+    /// Loads the progress file at `path`, starting empty when it does not exist yet.
+    pub fn load(path: &Path) -> EthersResult<Self> {
+        let rows = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Progress {
+            path: path.to_path_buf(),
+            rows,
+        })
+    }
+
+    pub fn get(&self, row: usize) -> Option<&T> {
+        self.rows.get(&row)
+    }
+
+    pub fn rows(&self) -> Vec<(usize, T)> {
+        self.rows
+            .iter()
+            .map(|(row, entry)| (*row, entry.clone()))
+            .collect()
+    }
+
+    /// Records `entry` for `row` and flushes the whole file via a temp file + rename.
+    pub fn set(&mut self, row: usize, entry: T) -> EthersResult<()> {
+        self.rows.insert(row, entry);
+
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self.rows)?)?;
+        fs::rename(&tmp, &self.path)?;
+
+        Ok(())
+    }
+}
+
+/// A row of a job that sends one transaction per row, such as a mint or a transfer.
+///
+/// Its transaction hash is recorded before waiting on it, so a resumed run looks the
+/// transaction up instead of sending it again. A row is only sent again once its transaction
+/// is known not to have gone through: it reverted, or it was dropped and its nonce reused.
+pub trait SentRow: Serialize + DeserializeOwned + Clone {
+    fn row(&self) -> usize;
+    fn tx_hash(&self) -> Option<H256>;
+    fn nonce(&self) -> Option<U256>;
+    /// The transaction went through.
+    fn is_done(&self) -> bool;
+    /// The transaction in `tx_hash` is known not to have gone through.
+    fn is_failed(&self) -> bool;
+    fn set_sent(&mut self, tx_hash: Option<H256>, nonce: Option<U256>);
+    fn set_failed(&mut self, failed: bool);
+    fn set_error(&mut self, error: Option<String>);
+}
+
+/// Sent, but whether it went through is not known yet.
+pub fn is_unsettled<T: SentRow>(result: &T) -> bool {
+    result.tx_hash().is_some() && !result.is_done() && !result.is_failed()
+}
+
+/// Safe to send: nothing was recorded or broadcast, or what was broadcast did not go through.
+pub fn needs_send<T: SentRow>(result: Option<&T>) -> bool {
+    match result {
+        Some(result) => !result.is_done() && (result.tx_hash().is_none() || result.is_failed()),
+        None => true,
+    }
+}
+
+/// Marks `result` failed when `receipt` reverted, and otherwise hands it to `on_mined` to
+/// record what the transaction did.
+pub fn apply_receipt<T: SentRow>(
+    result: &mut T,
+    receipt: TransactionReceipt,
+    on_mined: impl FnOnce(&mut T, TransactionReceipt),
+) {
+    if receipt.status == Some(0.into()) {
+        result.set_failed(true);
+        result.set_error(Some("transaction reverted".to_string()));
+        return;
+    }
+
+    result.set_error(None);
+    on_mined(result, receipt);
+}
+
+/// Settles every row a previous run left unsettled by looking its transaction up, leaving
+/// rows whose transaction may still be mined as they are.
+pub async fn resume<T: SentRow>(
+    client: &NonceClient,
+    progress: &Mutex<Progress<T>>,
+    on_mined: impl Fn(&mut T, TransactionReceipt),
+) -> EthersResult<()> {
+    let recorded = progress.lock().unwrap().rows();
+    for (row, mut result) in recorded {
+        if !is_unsettled(&result) {
+            continue;
+        }
+
+        let tx_hash = result.tx_hash().unwrap();
+        match sent_status(client, tx_hash, result.nonce()).await? {
+            SentStatus::Mined(receipt) => apply_receipt(&mut result, *receipt, &on_mined),
+            SentStatus::Dropped => {
+                result.set_failed(true);
+                result.set_error(Some("transaction dropped".to_string()));
+            }
+            SentStatus::Pending => {
+                tracing::warn!(row, ?tx_hash, "transaction not confirmed yet, skipping");
+                continue;
+            }
+        }
+        progress.lock().unwrap().set(row, result)?;
+    }
+
+    Ok(())
+}
+
+/// Records the broadcast `tx` in `result` and the progress file, then waits for it. A
+/// dropped transaction is left unsettled; the next run decides whether to send it again.
+pub async fn track<T: SentRow>(
+    client: &NonceClient,
+    progress: &Mutex<Progress<T>>,
+    result: &mut T,
+    tx: PendingTransaction<'_, <NonceClient as Middleware>::Provider>,
+    on_mined: impl FnOnce(&mut T, TransactionReceipt),
+) -> EthersResult<()> {
+    result.set_sent(Some(*tx), None);
+    progress.lock().unwrap().set(result.row(), result.clone())?;
+    result.set_sent(Some(*tx), sent_nonce(client, *tx).await);
+    progress.lock().unwrap().set(result.row(), result.clone())?;
+
+    match tx.await? {
+        Some(receipt) => apply_receipt(result, receipt, on_mined),
+        None => result.set_error(Some("transaction dropped".to_string())),
+    }
+
+    Ok(())
+}
+
+/// What became of a transaction a previous run broadcast.
+#[derive(Clone, Debug)]
+pub enum SentStatus {
+    Mined(Box<TransactionReceipt>),
+    /// Not mined yet, or nothing proves it never will be; sending again could duplicate it.
+    Pending,
+    /// Never mined, and its nonce has since been used by another transaction, so it never
+    /// will be.
+    Dropped,
+}
+
+/// Looks up the transaction `tx_hash` sent by `client`'s wallet with `nonce`, when known.
+pub async fn sent_status(
+    client: &NonceClient,
+    tx_hash: H256,
+    nonce: Option<U256>,
+) -> EthersResult<SentStatus> {
+    let provider = client.provider();
+    // Read the mined nonce before the receipt: if it already passed our nonce and there is
+    // still no receipt, another transaction took the slot.
+    let mined_nonce = provider
+        .get_transaction_count(client.inner().address(), Some(BlockNumber::Latest.into()))
+        .await?;
+    if let Some(receipt) = provider.get_transaction_receipt(tx_hash).await? {
+        return Ok(SentStatus::Mined(Box::new(receipt)));
+    }
+
+    let nonce = match provider.get_transaction(tx_hash).await? {
+        Some(tx) => Some(tx.nonce),
+        None => nonce,
+    };
+    match nonce {
+        Some(nonce) if nonce < mined_nonce => Ok(SentStatus::Dropped),
+        _ => Ok(SentStatus::Pending),
+    }
+}
+
+/// Nonce of a just broadcast transaction, recorded so [`sent_status`] can still tell whether
+/// it was dropped after the node forgets it.
+pub async fn sent_nonce(client: &NonceClient, tx_hash: H256) -> Option<U256> {
+    match client.provider().get_transaction(tx_hash).await {
+        Ok(tx) => tx.map(|tx| tx.nonce),
+        Err(e) => {
+            tracing::warn!(?tx_hash, error = ?e, "cannot read nonce of sent transaction");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    struct Row {
+        tx_hash: Option<H256>,
+        nonce: Option<U256>,
+        done: bool,
+        failed: bool,
+        error: Option<String>,
+    }
+
+    impl SentRow for Row {
+        fn row(&self) -> usize {
+            0
+        }
+        fn tx_hash(&self) -> Option<H256> {
+            self.tx_hash
+        }
+        fn nonce(&self) -> Option<U256> {
+            self.nonce
+        }
+        fn is_done(&self) -> bool {
+            self.done
+        }
+        fn is_failed(&self) -> bool {
+            self.failed
+        }
+        fn set_sent(&mut self, tx_hash: Option<H256>, nonce: Option<U256>) {
+            self.tx_hash = tx_hash;
+            self.nonce = nonce;
+        }
+        fn set_failed(&mut self, failed: bool) {
+            self.failed = failed;
+        }
+        fn set_error(&mut self, error: Option<String>) {
+            self.error = error;
+        }
+    }
+
+    fn row(tx_hash: Option<u64>, done: bool, failed: bool, error: bool) -> Row {
+        Row {
+            tx_hash: tx_hash.map(H256::from_low_u64_be),
+            nonce: None,
+            done,
+            failed,
+            error: error.then(|| "error".to_string()),
+        }
+    }
+
+    fn receipt(status: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            status: Some(status.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sends_new_rows() {
+        assert!(needs_send::<Row>(None));
+    }
+
+    #[test]
+    fn skips_rows_that_went_through() {
+        let done = row(Some(1), true, false, false);
+        assert!(!needs_send(Some(&done)));
+        assert!(!is_unsettled(&done));
+    }
+
+    #[test]
+    fn resends_rows_that_never_broadcast() {
+        let failed_to_send = row(None, false, false, true);
+        assert!(needs_send(Some(&failed_to_send)));
+        assert!(!is_unsettled(&failed_to_send));
+    }
+
+    #[test]
+    fn looks_up_broadcast_rows_instead_of_resending() {
+        // Interrupted while waiting, waiting failed, or the node reported it dropped.
+        for error in [false, true] {
+            let sent = row(Some(1), false, false, error);
+            assert!(!needs_send(Some(&sent)));
+            assert!(is_unsettled(&sent));
+        }
+    }
+
+    #[test]
+    fn resends_rows_whose_transaction_did_not_go_through() {
+        let reverted = row(Some(1), false, true, true);
+        assert!(needs_send(Some(&reverted)));
+        assert!(!is_unsettled(&reverted));
+    }
+
+    #[test]
+    fn fails_reverted_receipts() {
+        let mut sent = row(Some(1), false, false, false);
+        apply_receipt(&mut sent, receipt(0), |_, _| panic!("reverted"));
+        assert!(sent.failed);
+        assert_eq!(sent.error.as_deref(), Some("transaction reverted"));
+        assert!(needs_send(Some(&sent)));
+    }
+
+    #[test]
+    fn records_mined_receipts() {
+        let mut sent = row(Some(1), false, false, true);
+        apply_receipt(&mut sent, receipt(1), |row, _| row.done = true);
+        assert!(sent.done);
+        assert!(sent.error.is_none());
+        assert!(!needs_send(Some(&sent)));
+    }
+}
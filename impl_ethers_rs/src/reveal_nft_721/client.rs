@@ -1,8 +1,12 @@
-use crate::{deploy_contract, query_contract, transaction_contract, EthersResult};
+use crate::erc721::{Erc721, Erc721Enumerable, Erc721Metadata, Erc721Mintable, Erc721Transferable};
+use crate::event::{ChainlinkFulfilledEvent, ChainlinkRequestedEvent, OracleRequestEvent};
+use crate::{deploy_contract, Account, Error, EthersResult};
+use chrono::{DateTime, Duration, TimeZone, Timelike, Utc};
 use ethers::abi::Abi;
+use ethers::contract::parse_log;
 use ethers::prelude::*;
+use futures::future::try_join_all;
 use prelude::*;
-use std::env;
 
 #[derive(Clone, Debug)]
 pub struct Client {
@@ -15,139 +19,301 @@ pub struct Client {
 
 impl Client {
     pub fn new(network: Network) -> Self {
-        let wallet_address = env::var("WALLET_ADDRESS").expect("WALLET_ADDRESS must be set");
-        let wallet_secret = env::var("WALLET_SECRET").expect("WALLET_SECRET must be set");
+        Self::with_address(
+            network,
+            network.reveal_nft_address().parse::<Address>().unwrap(),
+            Account::from_env(),
+        )
+    }
 
+    /// Binds to the collection at `address`, signing transactions with `account`.
+    pub fn with_address(network: Network, address: Address, account: Account) -> Self {
         Client {
-            wallet_address: wallet_address.parse::<Address>().unwrap(),
-            wallet_secret,
-            address: network.reveal_nft_address().parse::<Address>().unwrap(),
+            wallet_address: account.address,
+            wallet_secret: account.secret,
+            address,
             abi: serde_json::from_str(include_str!("abi.json").trim()).unwrap(),
             network,
         }
     }
 
-    pub async fn name(&self) -> EthersResult<String> {
-        let res = query_contract(
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .method::<_, String>("name", ())?
-        .call()
-        .await?;
-        Ok(res)
+    /// UTC hour of the last oracle response, or -1 before the oracle has answered.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn get_current_hour(&self) -> EthersResult<i64> {
+        let res = self
+            .query()
+            .method::<_, I256>("getCurrentHour", ())?
+            .call()
+            .await?;
+        Ok(res.as_i64())
     }
 
-    pub async fn total_supply(&self) -> EthersResult<u128> {
-        let res = query_contract(
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .method::<_, u128>("totalSupply", ())?
-        .call()
-        .await?;
+    /// Last `(now, timestamp)` reported by the time adapter.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn time_adapter_response(&self) -> EthersResult<(String, U256)> {
+        let res = self
+            .query()
+            .method::<_, (String, U256)>("timeAdapterResponse", ())?
+            .call()
+            .await?;
         Ok(res)
     }
 
-    pub async fn get_current_hour(&self) -> EthersResult<u128> {
-        let res = query_contract(
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .method::<_, u128>("getCurrentHour", ())?
-        .call()
-        .await?;
-        Ok(res)
+    /// Reveal state of `token_ids` as `tokenURI` currently sees it, plus when the wall clock
+    /// next crosses a reveal window boundary.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn reveal_status(&self, token_ids: Vec<U256>) -> EthersResult<RevealStatus> {
+        let (oracle_now, timestamp) = self.time_adapter_response().await?;
+        let oracle_timestamp = if timestamp.is_zero() {
+            None
+        } else {
+            Utc.timestamp_opt(timestamp.as_u64() as i64, 0).single()
+        };
+        let revealed = oracle_timestamp
+            .map(|timestamp| is_reveal_hour(timestamp.hour()))
+            .unwrap_or(false);
+
+        let token_uris =
+            try_join_all(token_ids.iter().map(|token_id| self.token_uri(*token_id))).await?;
+        let tokens = token_ids
+            .into_iter()
+            .zip(token_uris)
+            .map(|(token_id, token_uri)| TokenRevealStatus {
+                token_id,
+                revealed,
+                token_uri,
+            })
+            .collect();
+
+        let now = Utc::now();
+        Ok(RevealStatus {
+            oracle_now,
+            oracle_timestamp,
+            oracle_age: oracle_timestamp.map(|timestamp| now - timestamp),
+            revealed,
+            next_flip: next_window_boundary(now),
+            tokens,
+        })
     }
 
-    pub async fn mint(&self, hash: String) -> EthersResult<()> {
-        let call = transaction_contract(
-            self.wallet_secret.to_owned(),
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .await
-        .method::<_, H256>("mint", hash)?
-        .gas(GAS_LIMIT)
-        .gas_price(GAS_PRICE);
+    /// Asks the oracle for the current time and returns the Chainlink request it opened,
+    /// which `cancel_request` needs if the oracle never answers. Fails when the transaction
+    /// was dropped, reverted or opened no request.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn update_time(&self) -> EthersResult<ChainlinkRequest> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>("updateTime", ())?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let tx_hash = *tx;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        let receipt = receipt
+            .ok_or_else(|| Error::Internal(format!("updateTime {:?} was dropped", tx_hash)))?;
+        if receipt.status == Some(0.into()) {
+            return Err(Error::Internal(format!(
+                "updateTime {:?} reverted",
+                tx_hash
+            )));
+        }
+        let id = receipt
+            .logs
+            .iter()
+            .find_map(|log| parse_log::<ChainlinkRequestedEvent>(log.clone()).ok())
+            .map(|event| H256::from(event.id))
+            .ok_or_else(|| {
+                Error::Internal(format!(
+                    "updateTime {:?} opened no chainlink request",
+                    tx_hash
+                ))
+            })?;
+        let expiration = receipt
+            .logs
+            .iter()
+            .filter_map(|log| parse_log::<OracleRequestEvent>(log.clone()).ok())
+            .find(|event| H256::from(event.request_id) == id)
+            .map(|event| event.cancel_expiration);
+
+        Ok(ChainlinkRequest {
+            id,
+            expiration,
+            block: receipt
+                .block_number
+                .map(|block| block.as_u64())
+                .unwrap_or(0),
+        })
+    }
+
+    /// Whether `fulfillUpdateTime` has landed for `request_id`, looking at
+    /// `ChainlinkFulfilled` logs since `from_block`.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn is_fulfilled(&self, request_id: H256, from_block: u64) -> EthersResult<bool> {
+        let events = self
+            .query()
+            .event::<ChainlinkFulfilledEvent>()
+            .topic1(request_id)
+            .from_block(from_block)
+            .query()
+            .await?;
+        Ok(!events.is_empty())
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn chainlink_config(&self) -> EthersResult<ChainlinkConfig> {
+        let query = self.query();
+        let token = query
+            .method::<_, Address>("getChainlinkTokenString", ())?
+            .call()
+            .await?;
+        let oracle = query
+            .method::<_, Address>("oracleAddress", ())?
+            .call()
+            .await?;
+        let job_id = query
+            .method::<_, [u8; 32]>("timeAdapterJobId", ())?
+            .call()
+            .await?;
+        let fee = query.method::<_, U256>("chainlinkFee", ())?.call().await?;
+
+        Ok(ChainlinkConfig {
+            token,
+            oracle,
+            job_id: String::from_utf8_lossy(&job_id)
+                .trim_end_matches('\0')
+                .to_string(),
+            fee,
+        })
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn set_chainlink_token(&self, address: Address) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>("setChainlinkTokenString", address)?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
         let tx = call.send().await?;
         let receipt = tx.await?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
 
-    pub async fn update_time(&self) -> EthersResult<()> {
-        let call = transaction_contract(
-            self.wallet_secret.to_owned(),
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .await
-        .method::<_, H256>("updateTime", ())?
-        .gas(GAS_LIMIT)
-        .gas_price(GAS_PRICE);
+    /// `fee` is in LINK wei paid to the oracle per request.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn set_chainlink_fee(&self, fee: U256) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>("setChainlinkFee", fee)?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
         let tx = call.send().await?;
         let receipt = tx.await?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
 
-    pub async fn transfer(&self, to: Address, token_id: u128) -> EthersResult<()> {
-        let call = transaction_contract(
-            self.wallet_secret.to_owned(),
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .await
-        .method::<_, H256>("safeTransferFrom", (self.wallet_address, to, token_id))?
-        .gas(GAS_LIMIT)
-        .gas_price(GAS_PRICE);
+    #[tracing::instrument(skip(self), err)]
+    pub async fn set_oracle_address(&self, oracle: Address) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>("setOracleAddress", oracle)?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
         let tx = call.send().await?;
         let receipt = tx.await?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
 
-    pub async fn set_approval_for_all(&self) -> EthersResult<()> {
-        let call = transaction_contract(
-            self.wallet_secret.to_owned(),
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .await
-        .method::<_, H256>(
-            "setApprovalForAll",
-            (
-                self.network
-                    .nft_market_address()
-                    .parse::<Address>()
-                    .unwrap(),
-                true,
-            ),
-        )?
-        .gas(GAS_LIMIT)
-        .gas_price(GAS_PRICE);
+    /// Accepts the job id as shown by the Chainlink node, with or without hyphens.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn set_time_adapter_job_id(&self, job_id: String) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>("setTimeAdapterJobId", job_id.replace('-', ""))?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+
+    /// Cancels an unanswered request after its `expiration` and refunds the fee.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn cancel_request(
+        &self,
+        request_id: H256,
+        callback_function_id: [u8; 4],
+        expiration: U256,
+    ) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>(
+                "cancelRequest",
+                (request_id, callback_function_id, expiration),
+            )?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
         let tx = call.send().await?;
         let receipt = tx.await?;
 
-        println!("{:?}", receipt);
+        tracing::info!(?receipt, "transaction mined");
 
         Ok(())
     }
 
+    #[tracing::instrument(skip(self), err)]
+    pub async fn withdraw_link(&self) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>("withdrawLink", ())?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    pub async fn set_timestamp_for_debug(&self, timestamp: U256) -> EthersResult<()> {
+        let call = self
+            .transaction()
+            .await
+            .method::<_, H256>("setTimestampForDebug", timestamp)?
+            .gas(GAS_LIMIT)
+            .gas_price(GAS_PRICE);
+        let tx = call.send().await?;
+        let receipt = tx.await?;
+
+        tracing::info!(?receipt, "transaction mined");
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self), err)]
     pub async fn deploy(&self) -> EthersResult<()> {
         let contract = deploy_contract(
             self.wallet_secret.to_owned(),
@@ -162,3 +328,164 @@ impl Client {
         Ok(())
     }
 }
+
+impl Erc721 for Client {
+    fn network(&self) -> Network {
+        self.network
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn abi(&self) -> &Abi {
+        &self.abi
+    }
+
+    fn wallet_address(&self) -> Address {
+        self.wallet_address
+    }
+
+    fn wallet_secret(&self) -> String {
+        self.wallet_secret.to_owned()
+    }
+}
+
+impl Erc721Transferable for Client {}
+
+impl Erc721Metadata for Client {}
+
+impl Erc721Enumerable for Client {}
+
+impl Erc721Mintable for Client {}
+
+/// Selector of the callback `updateTime` registers, needed to cancel its requests.
+pub fn fulfill_update_time_selector() -> [u8; 4] {
+    ethers::utils::id("fulfillUpdateTime(bytes32,string,uint256)")
+}
+
+/// A pending `updateTime` request. `expiration` comes from the oracle's `OracleRequest` log
+/// and is `None` when the oracle does not emit one.
+#[derive(Clone, Debug)]
+pub struct ChainlinkRequest {
+    pub id: H256,
+    pub expiration: Option<U256>,
+    pub block: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ChainlinkConfig {
+    pub token: Address,
+    pub oracle: Address,
+    pub job_id: String,
+    pub fee: U256,
+}
+
+/// `tokenURI` returns the real content hash while the oracle-reported hour is in
+/// `[REVEAL_START_HOUR, REVEAL_END_HOUR)` UTC, and the placeholder otherwise.
+pub const REVEAL_START_HOUR: u32 = 3;
+pub const REVEAL_END_HOUR: u32 = 10;
+
+pub fn is_reveal_hour(hour: u32) -> bool {
+    (REVEAL_START_HOUR..REVEAL_END_HOUR).contains(&hour)
+}
+
+/// The next time after `now` at which a fresh oracle response would flip the reveal state.
+pub fn next_window_boundary(now: DateTime<Utc>) -> DateTime<Utc> {
+    let hour = if is_reveal_hour(now.hour()) {
+        REVEAL_END_HOUR
+    } else {
+        REVEAL_START_HOUR
+    };
+    let today = now.date_naive().and_hms_opt(hour, 0, 0).unwrap();
+    let boundary = Utc.from_utc_datetime(&today);
+    if boundary > now {
+        boundary
+    } else {
+        boundary + Duration::days(1)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RevealStatus {
+    pub oracle_now: String,
+    pub oracle_timestamp: Option<DateTime<Utc>>,
+    pub oracle_age: Option<Duration>,
+    pub revealed: bool,
+    pub next_flip: DateTime<Utc>,
+    pub tokens: Vec<TokenRevealStatus>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TokenRevealStatus {
+    pub token_id: U256,
+    pub revealed: bool,
+    pub token_uri: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn reveal_hours_include_start_and_exclude_end() {
+        assert!(!is_reveal_hour(REVEAL_START_HOUR - 1));
+        assert!(is_reveal_hour(REVEAL_START_HOUR));
+        assert!(is_reveal_hour(REVEAL_END_HOUR - 1));
+        assert!(!is_reveal_hour(REVEAL_END_HOUR));
+        assert!(!is_reveal_hour(0));
+        assert!(!is_reveal_hour(23));
+    }
+
+    #[test]
+    fn boundary_before_the_window_is_its_start() {
+        assert_eq!(
+            next_window_boundary(at("2023-05-01T02:59:59Z")),
+            at("2023-05-01T03:00:00Z")
+        );
+        assert_eq!(
+            next_window_boundary(at("2023-05-01T00:00:00Z")),
+            at("2023-05-01T03:00:00Z")
+        );
+    }
+
+    #[test]
+    fn boundary_at_the_exact_start_is_the_end() {
+        assert_eq!(
+            next_window_boundary(at("2023-05-01T03:00:00Z")),
+            at("2023-05-01T10:00:00Z")
+        );
+        assert_eq!(
+            next_window_boundary(at("2023-05-01T09:59:59.999Z")),
+            at("2023-05-01T10:00:00Z")
+        );
+    }
+
+    #[test]
+    fn boundary_at_the_exact_end_is_the_next_start() {
+        assert_eq!(
+            next_window_boundary(at("2023-05-01T10:00:00Z")),
+            at("2023-05-02T03:00:00Z")
+        );
+    }
+
+    #[test]
+    fn boundary_rolls_over_days_months_and_years() {
+        assert_eq!(
+            next_window_boundary(at("2023-05-01T23:59:59Z")),
+            at("2023-05-02T03:00:00Z")
+        );
+        assert_eq!(
+            next_window_boundary(at("2023-02-28T12:00:00Z")),
+            at("2023-03-01T03:00:00Z")
+        );
+        assert_eq!(
+            next_window_boundary(at("2023-12-31T10:30:00Z")),
+            at("2024-01-01T03:00:00Z")
+        );
+    }
+}
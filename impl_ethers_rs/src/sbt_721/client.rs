@@ -1,11 +1,12 @@
-use crate::{deploy_contract, query_contract, transaction_contract, EthersResult};
+use crate::erc721::{Erc721, Erc721Enumerable, Erc721Metadata, Erc721Mintable};
+use crate::{deploy_contract, Account, EthersResult};
 use ethers::abi::Abi;
 use ethers::prelude::*;
 use prelude::*;
-use std::env;
 
 #[derive(Clone, Debug)]
 pub struct Client {
+    wallet_address: Address,
     wallet_secret: String,
     address: Address,
     abi: Abi,
@@ -14,59 +15,25 @@ pub struct Client {
 
 impl Client {
     pub fn new(network: Network) -> Self {
-        let wallet_secret = env::var("WALLET_SECRET").expect("WALLET_SECRET must be set");
+        Self::with_address(
+            network,
+            network.sbt_721_address().parse::<Address>().unwrap(),
+            Account::from_env(),
+        )
+    }
 
+    /// Binds to the collection at `address`, signing transactions with `account`.
+    pub fn with_address(network: Network, address: Address, account: Account) -> Self {
         Client {
-            wallet_secret,
-            address: network.sbt_721_address().parse::<Address>().unwrap(),
+            wallet_address: account.address,
+            wallet_secret: account.secret,
+            address,
             abi: serde_json::from_str(include_str!("abi.json").trim()).unwrap(),
             network,
         }
     }
 
-    pub async fn name(&self) -> EthersResult<String> {
-        let res = query_contract(
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .method::<_, String>("name", ())?
-        .call()
-        .await?;
-        Ok(res)
-    }
-
-    pub async fn total_supply(&self) -> EthersResult<u128> {
-        let res = query_contract(
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .method::<_, u128>("totalSupply", ())?
-        .call()
-        .await?;
-        Ok(res)
-    }
-
-    pub async fn mint(&self, hash: String) -> EthersResult<()> {
-        let call = transaction_contract(
-            self.wallet_secret.to_owned(),
-            self.address.to_owned(),
-            self.abi.to_owned(),
-            self.network.to_owned(),
-        )
-        .await
-        .method::<_, H256>("mint", hash)?
-        .gas(GAS_LIMIT)
-        .gas_price(GAS_PRICE);
-        let tx = call.send().await?;
-        let receipt = tx.await?;
-
-        println!("{:?}", receipt);
-
-        Ok(())
-    }
-
+    #[tracing::instrument(skip(self), err)]
     pub async fn deploy(&self) -> EthersResult<()> {
         let contract = deploy_contract(
             self.wallet_secret.to_owned(),
@@ -81,3 +48,31 @@ impl Client {
         Ok(())
     }
 }
+
+impl Erc721 for Client {
+    fn network(&self) -> Network {
+        self.network
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn abi(&self) -> &Abi {
+        &self.abi
+    }
+
+    fn wallet_address(&self) -> Address {
+        self.wallet_address
+    }
+
+    fn wallet_secret(&self) -> String {
+        self.wallet_secret.to_owned()
+    }
+}
+
+impl Erc721Metadata for Client {}
+
+impl Erc721Enumerable for Client {}
+
+impl Erc721Mintable for Client {}
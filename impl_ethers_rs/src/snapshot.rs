@@ -0,0 +1,386 @@
+use crate::event::{TransferBatchEvent, TransferSingleEvent};
+use crate::nft_1155::client::expand_uri;
+use crate::{http_provider, query_contract, to_count, EthersResult, HttpProvider};
+use ethers::abi::Abi;
+use ethers::contract::Contract;
+use ethers::prelude::*;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use ipfs::resolver::Resolver;
+use prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// One holding in a snapshot. 721 collections have exactly one row per token; 1155
+/// collections have one row per (token, holder) with a non-zero balance.
+#[derive(Clone, Debug, Serialize)]
+pub struct SnapshotRow {
+    pub token_id: String,
+    pub owner: String,
+    pub balance: String,
+    pub token_uri: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub metadata_error: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub block: u64,
+    pub rows: Vec<SnapshotRow>,
+}
+
+impl Snapshot {
+    pub fn write_csv(&self, path: &Path) -> EthersResult<()> {
+        let mut writer = csv::Writer::from_path(path)?;
+        for row in self.rows.iter() {
+            writer.serialize(row)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn write_jsonl(&self, path: &Path) -> EthersResult<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        for row in self.rows.iter() {
+            serde_json::to_writer(&mut writer, row)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Every token of an enumerable 721 collection with its owner, token URI and metadata as of
+/// `block` (the latest block when `None`). Reads run `concurrency` tokens at a time.
+///
+/// This walks `tokenByIndex` itself rather than through [`crate::erc721::Erc721Enumerable`],
+/// whose reads are not pinned to a block.
+#[tracing::instrument(err)]
+pub async fn erc721(
+    network: Network,
+    address: Address,
+    block: Option<u64>,
+    concurrency: usize,
+) -> EthersResult<Snapshot> {
+    let abi: Abi = serde_json::from_str(include_str!("nft_721/abi.json").trim()).unwrap();
+    let contract = query_contract(address, abi, network);
+    let block = pinned_block(network, block).await?;
+    let resolver = Resolver::new()?;
+
+    let total_supply = to_count(
+        contract
+            .method::<_, U256>("totalSupply", ())?
+            .block(block)
+            .call()
+            .await?,
+    )?;
+    tracing::info!(block, total_supply, "taking snapshot");
+
+    let mut rows = stream::iter(0..total_supply)
+        .map(|index| {
+            let contract = &contract;
+            let resolver = &resolver;
+            async move {
+                let token_id = contract
+                    .method::<_, U256>("tokenByIndex", U256::from(index))?
+                    .block(block)
+                    .call()
+                    .await?;
+                let owner = contract
+                    .method::<_, Address>("ownerOf", token_id)?
+                    .block(block)
+                    .call()
+                    .await?;
+                let token_uri = contract
+                    .method::<_, String>("tokenURI", token_id)?
+                    .block(block)
+                    .call()
+                    .await?;
+                EthersResult::Ok((
+                    token_id,
+                    row(resolver, token_id, owner, U256::one(), token_uri).await,
+                ))
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .try_collect::<Vec<_>>()
+        .await?;
+    rows.sort_by_key(|(token_id, _)| *token_id);
+
+    Ok(Snapshot {
+        block,
+        rows: rows.into_iter().map(|(_, row)| row).collect(),
+    })
+}
+
+/// Every holder of every id up to `latestTokenId` in a 1155 collection as of `block` (the
+/// latest block when `None`). Holders are discovered from transfer logs since `from_block`
+/// and kept only while their balance at `block` is non-zero.
+#[tracing::instrument(err)]
+pub async fn erc1155(
+    network: Network,
+    address: Address,
+    block: Option<u64>,
+    from_block: u64,
+    concurrency: usize,
+) -> EthersResult<Snapshot> {
+    let abi: Abi = serde_json::from_str(include_str!("nft_1155/abi.json").trim()).unwrap();
+    let contract = query_contract(address, abi, network);
+    let block = pinned_block(network, block).await?;
+    let resolver = Resolver::new()?;
+
+    let latest_token_id = to_count(
+        contract
+            .method::<_, U256>("latestTokenId", ())?
+            .block(block)
+            .call()
+            .await?,
+    )?;
+    let holders = holders_1155(&contract, from_block, block).await?;
+    tracing::info!(block, latest_token_id, "taking snapshot");
+
+    let mut rows = stream::iter(1..=latest_token_id)
+        .map(|token_id| {
+            let contract = &contract;
+            let resolver = &resolver;
+            let holders = holders
+                .get(&U256::from(token_id))
+                .map(|holders| holders.iter().copied().collect::<Vec<_>>())
+                .unwrap_or_default();
+            async move {
+                let token_id = U256::from(token_id);
+                let balances = contract
+                    .method::<_, Vec<U256>>(
+                        "balanceOfBatch",
+                        (holders.clone(), vec![token_id; holders.len()]),
+                    )?
+                    .block(block)
+                    .call()
+                    .await?;
+                let token_uri = expand_uri(
+                    &contract
+                        .method::<_, String>("uri", token_id)?
+                        .block(block)
+                        .call()
+                        .await?,
+                    token_id,
+                );
+
+                let mut rows = vec![];
+                for (owner, balance) in holders.into_iter().zip(balances) {
+                    if !balance.is_zero() {
+                        rows.push((
+                            token_id,
+                            row(resolver, token_id, owner, balance, token_uri.clone()).await,
+                        ));
+                    }
+                }
+                EthersResult::Ok(rows)
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<(U256, SnapshotRow)>>();
+    rows.sort_by(|(a, x), (b, y)| a.cmp(b).then_with(|| x.owner.cmp(&y.owner)));
+
+    Ok(Snapshot {
+        block,
+        rows: rows.into_iter().map(|(_, row)| row).collect(),
+    })
+}
+
+/// Pins every read of a snapshot to one block so it is consistent even while the chain moves.
+async fn pinned_block(network: Network, block: Option<u64>) -> EthersResult<u64> {
+    match block {
+        Some(block) => Ok(block),
+        None => Ok(http_provider(network).get_block_number().await?.as_u64()),
+    }
+}
+
+/// Every address that has ever received each token id, from `TransferSingle` and
+/// `TransferBatch` logs between `from_block` and `to_block`.
+async fn holders_1155(
+    contract: &Contract<HttpProvider>,
+    from_block: u64,
+    to_block: u64,
+) -> EthersResult<BTreeMap<U256, BTreeSet<Address>>> {
+    let singles = contract
+        .event::<TransferSingleEvent>()
+        .from_block(from_block)
+        .to_block(to_block)
+        .query()
+        .await?;
+    let batches = contract
+        .event::<TransferBatchEvent>()
+        .from_block(from_block)
+        .to_block(to_block)
+        .query()
+        .await?;
+
+    Ok(collect_holders(singles, batches))
+}
+
+/// Recipients per token id. Burns go to the zero address, which is left out since
+/// `balanceOf(address(0))` reverts.
+fn collect_holders(
+    singles: Vec<TransferSingleEvent>,
+    batches: Vec<TransferBatchEvent>,
+) -> BTreeMap<U256, BTreeSet<Address>> {
+    let mut holders: BTreeMap<U256, BTreeSet<Address>> = BTreeMap::new();
+    for event in singles.into_iter().filter(|event| !event.to.is_zero()) {
+        holders.entry(event.id).or_default().insert(event.to);
+    }
+    for event in batches.into_iter().filter(|event| !event.to.is_zero()) {
+        for id in event.ids {
+            holders.entry(id).or_default().insert(event.to);
+        }
+    }
+    holders
+}
+
+async fn row(
+    resolver: &Resolver,
+    token_id: U256,
+    owner: Address,
+    balance: U256,
+    token_uri: String,
+) -> SnapshotRow {
+    let mut row = SnapshotRow {
+        token_id: token_id.to_string(),
+        owner: format!("{:?}", owner),
+        balance: balance.to_string(),
+        token_uri,
+        name: None,
+        description: None,
+        image: None,
+        metadata_error: None,
+    };
+
+    match resolver.resolve(&row.token_uri).await {
+        Ok(metadata) => {
+            row.name = Some(metadata.name);
+            row.description = Some(metadata.description);
+            row.image = Some(metadata.image);
+        }
+        Err(e) => row.metadata_error = Some(format!("{:?}", e)),
+    }
+
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn address(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn single(from: u64, to: u64, id: u64) -> TransferSingleEvent {
+        TransferSingleEvent {
+            operator: address(1),
+            from: address(from),
+            to: address(to),
+            id: id.into(),
+            value: U256::one(),
+        }
+    }
+
+    fn batch(from: u64, to: u64, ids: &[u64]) -> TransferBatchEvent {
+        TransferBatchEvent {
+            operator: address(1),
+            from: address(from),
+            to: address(to),
+            ids: ids.iter().map(|id| U256::from(*id)).collect(),
+            values: vec![U256::one(); ids.len()],
+        }
+    }
+
+    fn snapshot() -> Snapshot {
+        let row = |token_id: &str, name: Option<&str>, error: Option<&str>| SnapshotRow {
+            token_id: token_id.to_string(),
+            owner: format!("{:?}", address(2)),
+            balance: "1".to_string(),
+            token_uri: "ipfs://Qm".to_string(),
+            name: name.map(str::to_string),
+            description: name.map(|_| "a, \"quoted\" description".to_string()),
+            image: None,
+            metadata_error: error.map(str::to_string),
+        };
+        Snapshot {
+            block: 10,
+            rows: vec![row("1", Some("One"), None), row("2", None, Some("timeout"))],
+        }
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("snapshot-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn collects_holders_from_single_and_batch_transfers() {
+        let holders = collect_holders(
+            vec![single(0, 2, 1), single(2, 3, 1), single(0, 2, 1)],
+            vec![batch(0, 4, &[1, 2])],
+        );
+        assert_eq!(
+            holders,
+            BTreeMap::from([
+                (
+                    U256::from(1),
+                    BTreeSet::from([address(2), address(3), address(4)])
+                ),
+                (U256::from(2), BTreeSet::from([address(4)])),
+            ])
+        );
+    }
+
+    #[test]
+    fn leaves_burns_out_of_holders() {
+        let holders = collect_holders(vec![single(2, 0, 1)], vec![batch(4, 0, &[2])]);
+        assert!(holders.is_empty());
+    }
+
+    #[test]
+    fn writes_csv_with_a_header() {
+        let path = temp_path("rows.csv");
+        snapshot().write_csv(&path).unwrap();
+        let csv = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            "token_id,owner,balance,token_uri,name,description,image,metadata_error"
+        );
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].ends_with(",One,\"a, \"\"quoted\"\" description\",,"));
+        assert!(lines[2].ends_with(",,,,timeout"));
+    }
+
+    #[test]
+    fn writes_one_json_object_per_line() {
+        let path = temp_path("rows.jsonl");
+        snapshot().write_jsonl(&path).unwrap();
+        let jsonl = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let rows = jsonl
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["token_id"], "1");
+        assert_eq!(rows[0]["name"], "One");
+        assert_eq!(rows[1]["name"], serde_json::Value::Null);
+        assert_eq!(rows[1]["metadata_error"], "timeout");
+    }
+}
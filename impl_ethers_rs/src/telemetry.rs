@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use ethers::prelude::*;
+pub use prelude::telemetry::{init_metrics, init_tracing};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::time::Instant;
+use tracing::Instrument;
+
+/// JSON-RPC transport wrapper that opens a span and records latency/error metrics for every request.
+#[derive(Debug, Clone)]
+pub struct Metered<C> {
+    inner: C,
+}
+
+impl<C> Metered<C> {
+    pub fn new(inner: C) -> Self {
+        Metered { inner }
+    }
+}
+
+#[async_trait]
+impl<C: JsonRpcClient> JsonRpcClient for Metered<C> {
+    type Error = C::Error;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let started = Instant::now();
+        let res = self
+            .inner
+            .request(method, params)
+            .instrument(tracing::debug_span!("rpc", method))
+            .await;
+
+        let method = method.to_string();
+        metrics::increment_counter!("ethereum_rpc_requests_total", "method" => method.clone());
+        metrics::histogram!(
+            "ethereum_rpc_duration_seconds",
+            started.elapsed().as_secs_f64(),
+            "method" => method.clone()
+        );
+        if let Err(e) = &res {
+            metrics::increment_counter!("ethereum_rpc_errors_total", "method" => method.clone());
+            tracing::warn!(%method, error = ?e, "rpc request failed");
+        }
+
+        res
+    }
+}
+
+impl<C: PubsubClient> PubsubClient for Metered<C> {
+    type NotificationStream = C::NotificationStream;
+
+    fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, Self::Error> {
+        self.inner.subscribe(id)
+    }
+
+    fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), Self::Error> {
+        self.inner.unsubscribe(id)
+    }
+}
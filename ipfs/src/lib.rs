@@ -5,9 +5,11 @@ use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs::File;
 use std::io::Read;
+use std::time::Instant;
 use url::Url;
 
-mod metadata;
+pub mod metadata;
+pub mod resolver;
 
 #[derive(Clone, Debug)]
 struct Adapter {
@@ -29,7 +31,24 @@ impl Adapter {
         }
     }
 
+    #[tracing::instrument(skip(self, byte), fields(size = byte.len()), err)]
     pub async fn upload(&self, byte: Bytes, name: String) -> IpfsResult<IpfsOutput> {
+        let started = Instant::now();
+        let res = self.add(byte, name).await;
+
+        metrics::increment_counter!("ipfs_uploads_total");
+        metrics::histogram!(
+            "ipfs_upload_duration_seconds",
+            started.elapsed().as_secs_f64()
+        );
+        if res.is_err() {
+            metrics::increment_counter!("ipfs_upload_errors_total");
+        }
+
+        res
+    }
+
+    async fn add(&self, byte: Bytes, name: String) -> IpfsResult<IpfsOutput> {
         let form = multipart::Form::new().part("file", Part::bytes(byte.to_vec()).file_name(name));
 
         let mut url = self.base_url.to_owned();
@@ -58,6 +77,7 @@ struct IpfsOutput {
     pub hash: String,
 }
 
+#[tracing::instrument(err)]
 pub async fn create_metadata_from_url(
     name: String,
     description: String,
@@ -75,13 +95,11 @@ pub async fn create_metadata_from_url(
     let metadata = metadata::Metadata::new(&name, &image_url, &description);
     let metadata = serde_json::to_string(&metadata).map_err(Error::from)?;
     let content_hash = ipfs.upload(Bytes::from(metadata), name.clone()).await?;
-    println!(
-        "metadata url: {:?}",
-        format!("ipfs://{}", content_hash.hash.clone())
-    );
+    println!("metadata url: ipfs://{}", content_hash.hash);
     Ok(())
 }
 
+#[tracing::instrument(err)]
 pub async fn create_metadata_from_file(
     name: String,
     description: String,
@@ -108,10 +126,7 @@ pub async fn create_metadata_from_file(
     );
     let metadata = serde_json::to_string(&metadata).map_err(Error::from)?;
     let content_hash = ipfs.upload(Bytes::from(metadata), name.clone()).await?;
-    println!(
-        "metadata url: {:?}",
-        format!("ipfs://{}", content_hash.hash.clone())
-    );
+    println!("metadata url: ipfs://{}", content_hash.hash);
 
     Ok(())
 }
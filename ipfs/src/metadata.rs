@@ -3,7 +3,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Metadata {
     pub name: String,
+    #[serde(default)]
     pub image: String,
+    #[serde(default)]
     pub description: String,
 }
 
@@ -0,0 +1,232 @@
+use crate::metadata::Metadata;
+use crate::{Error, IpfsResult};
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+const DEFAULT_GATEWAYS: &str = "https://ipfs.io,https://cloudflare-ipfs.com,https://dweb.link";
+
+/// Reads token metadata back from `ipfs://`, gateway, plain http(s) and `data:` URIs.
+///
+/// IPFS content is tried on each gateway in order until one answers within the timeout
+/// with valid content, which is then cached on disk by URI since it can never change.
+#[derive(Clone, Debug)]
+pub struct Resolver {
+    gateways: Vec<Url>,
+    timeout: Duration,
+    cache_dir: PathBuf,
+}
+
+impl Resolver {
+    pub fn new() -> IpfsResult<Self> {
+        let gateways = env::var("IPFS_GATEWAYS").unwrap_or_else(|_| DEFAULT_GATEWAYS.to_string());
+        let timeout = env::var("IPFS_GATEWAY_TIMEOUT_SECS")
+            .map(|v| {
+                v.parse::<u64>()
+                    .expect("IPFS_GATEWAY_TIMEOUT_SECS must be a number")
+            })
+            .unwrap_or(10);
+        let cache_dir = env::var("IPFS_CACHE_DIR").unwrap_or_else(|_| ".cache/ipfs".to_string());
+
+        Ok(Resolver {
+            gateways: parse_gateways(&gateways)?,
+            timeout: Duration::from_secs(timeout),
+            cache_dir: PathBuf::from(cache_dir),
+        })
+    }
+
+    /// Token metadata behind `uri`. A gateway answering with anything but metadata JSON,
+    /// such as an error page, counts as failed and is never cached.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn resolve(&self, uri: &str) -> IpfsResult<Metadata> {
+        self.fetch_with(uri, |body| Ok(serde_json::from_slice(body)?))
+            .await
+    }
+
+    /// Raw content behind `uri`.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn fetch(&self, uri: &str) -> IpfsResult<Vec<u8>> {
+        self.fetch_with(uri, |body| Ok(body.to_vec())).await
+    }
+
+    /// Content behind `uri` as read by `parse`; only content `parse` accepts is cached.
+    async fn fetch_with<T>(
+        &self,
+        uri: &str,
+        parse: impl Fn(&[u8]) -> IpfsResult<T>,
+    ) -> IpfsResult<T> {
+        if let Some(data) = uri.strip_prefix("data:") {
+            return parse(&decode_data_uri(data)?);
+        }
+
+        let path = match ipfs_path(uri) {
+            Some(path) => path,
+            None => return parse(&self.get(uri).await?),
+        };
+
+        let cache = self
+            .cache_dir
+            .join(hex::encode(Sha256::digest(path.as_bytes())));
+        if let Ok(body) = fs::read(&cache) {
+            match parse(&body) {
+                Ok(v) => {
+                    tracing::debug!(?cache, "cache hit");
+                    return Ok(v);
+                }
+                Err(e) => tracing::warn!(?cache, error = ?e, "invalid cache entry, refetching"),
+            }
+        }
+
+        let mut last_err = Error::Internal("no ipfs gateway configured".to_string());
+        for gateway in self.gateways.iter() {
+            let url = gateway_url(gateway, &path);
+            match self
+                .get(&url)
+                .await
+                .and_then(|body| Ok((parse(&body)?, body)))
+            {
+                Ok((v, body)) => {
+                    fs::create_dir_all(&self.cache_dir)?;
+                    fs::write(&cache, &body)?;
+                    return Ok(v);
+                }
+                Err(e) => {
+                    tracing::warn!(%url, error = ?e, "gateway failed, trying next");
+                    last_err = e;
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    /// An http(s) URL for `uri` that a browser can open, using the first gateway for IPFS.
+    pub fn http_url(&self, uri: &str) -> String {
+        match (ipfs_path(uri), self.gateways.first()) {
+            (Some(path), Some(gateway)) => gateway_url(gateway, &path),
+            _ => uri.to_string(),
+        }
+    }
+
+    async fn get(&self, url: &str) -> IpfsResult<Vec<u8>> {
+        let body = reqwest::Client::new()
+            .get(url)
+            .timeout(self.timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        Ok(body.to_vec())
+    }
+}
+
+/// Comma separated gateway URLs, skipping empty entries such as a trailing comma.
+fn parse_gateways(gateways: &str) -> IpfsResult<Vec<Url>> {
+    gateways
+        .split(',')
+        .map(str::trim)
+        .filter(|gateway| !gateway.is_empty())
+        .map(|gateway| {
+            gateway.parse::<Url>().map_err(|e| {
+                Error::Internal(format!("invalid IPFS_GATEWAYS entry {}: {}", gateway, e))
+            })
+        })
+        .collect()
+}
+
+/// `<cid>[/path]` for `ipfs://` URIs and for URLs of any gateway (`.../ipfs/<cid>[/path]`).
+fn ipfs_path(uri: &str) -> Option<String> {
+    if let Some(path) = uri.strip_prefix("ipfs://") {
+        let path = path.strip_prefix("ipfs/").unwrap_or(path);
+        return Some(path.to_string());
+    }
+
+    let url = uri.parse::<Url>().ok()?;
+    url.path()
+        .strip_prefix("/ipfs/")
+        .map(|path| path.to_string())
+}
+
+fn gateway_url(gateway: &Url, path: &str) -> String {
+    format!("{}/ipfs/{}", gateway.as_str().trim_end_matches('/'), path)
+}
+
+/// Decodes the part of a `data:` URI after the scheme, e.g. `application/json;base64,eyJ...`.
+fn decode_data_uri(data: &str) -> IpfsResult<Vec<u8>> {
+    let (header, body) = data
+        .split_once(',')
+        .ok_or_else(|| Error::Internal("invalid data uri".to_string()))?;
+
+    if header.ends_with(";base64") {
+        base64::engine::general_purpose::STANDARD
+            .decode(body)
+            .map_err(|e| Error::Internal(format!("base64 decode error: {:?}", e)))
+    } else {
+        Ok(percent_encoding::percent_decode_str(body).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gateway_lists() {
+        let gateways = parse_gateways(" https://ipfs.io, ,https://dweb.link/,").unwrap();
+        assert_eq!(
+            gateways.iter().map(Url::as_str).collect::<Vec<_>>(),
+            vec!["https://ipfs.io/", "https://dweb.link/"]
+        );
+        assert!(parse_gateways("").unwrap().is_empty());
+        assert!(parse_gateways("https://ipfs.io,not a url").is_err());
+    }
+
+    #[test]
+    fn finds_ipfs_paths() {
+        assert_eq!(ipfs_path("ipfs://QmHash").as_deref(), Some("QmHash"));
+        assert_eq!(
+            ipfs_path("ipfs://ipfs/QmHash/1.json").as_deref(),
+            Some("QmHash/1.json")
+        );
+        assert_eq!(
+            ipfs_path("https://gateway.pinata.cloud/ipfs/QmHash/meta").as_deref(),
+            Some("QmHash/meta")
+        );
+        assert_eq!(ipfs_path("https://example.com/token/1"), None);
+        assert_eq!(ipfs_path("data:application/json,{}"), None);
+        assert_eq!(ipfs_path("not a uri"), None);
+    }
+
+    #[test]
+    fn builds_gateway_urls() {
+        let gateway = "https://ipfs.io/".parse::<Url>().unwrap();
+        assert_eq!(
+            gateway_url(&gateway, "QmHash"),
+            "https://ipfs.io/ipfs/QmHash"
+        );
+    }
+
+    #[test]
+    fn decodes_base64_data_uris() {
+        // {"name":"a"}
+        let body = decode_data_uri("application/json;base64,eyJuYW1lIjoiYSJ9").unwrap();
+        assert_eq!(body, br#"{"name":"a"}"#);
+        assert!(decode_data_uri("application/json;base64,!!!").is_err());
+    }
+
+    #[test]
+    fn decodes_percent_encoded_data_uris() {
+        let body = decode_data_uri("application/json,%7B%22name%22%3A%22a%20b%22%7D").unwrap();
+        assert_eq!(body, br#"{"name":"a b"}"#);
+        assert_eq!(decode_data_uri("text/plain,plain").unwrap(), b"plain");
+    }
+
+    #[test]
+    fn rejects_data_uris_without_a_body() {
+        assert!(decode_data_uri("application/json;base64").is_err());
+    }
+}
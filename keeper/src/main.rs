@@ -0,0 +1,13 @@
+use dotenv::dotenv;
+use impl_ethers_rs::{keeper, telemetry};
+
+#[tokio::main]
+pub async fn main() {
+    dotenv().ok();
+    telemetry::init_tracing();
+    telemetry::init_metrics();
+
+    if let Err(e) = keeper::run(keeper::Config::from_env()).await {
+        tracing::error!("error: {:?}", e);
+    }
+}
@@ -0,0 +1,61 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use chrono_tz::Tz;
+use std::env;
+
+/// Source of the time reported to `RevealNft721`, shared by the time adapter server and the
+/// fulfiller. Read from the environment:
+///
+/// - `TIME_ADAPTER_TZ`: IANA timezone `now` is rendered in (`UTC`). The timestamp is unaffected.
+/// - `TIME_ADAPTER_FIXED_TIME`: RFC 3339 time to always answer with, for tests.
+#[derive(Clone, Debug)]
+pub struct Clock {
+    pub timezone: Tz,
+    pub fixed: Option<DateTime<Utc>>,
+}
+
+impl Clock {
+    pub fn from_env() -> Self {
+        let timezone = env::var("TIME_ADAPTER_TZ")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                v.parse::<Tz>()
+                    .expect("TIME_ADAPTER_TZ must be an IANA timezone")
+            })
+            .unwrap_or(Tz::UTC);
+        let fixed = env::var("TIME_ADAPTER_FIXED_TIME")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                DateTime::parse_from_rfc3339(&v)
+                    .expect("TIME_ADAPTER_FIXED_TIME must be an RFC 3339 time")
+                    .with_timezone(&Utc)
+            });
+
+        Clock { timezone, fixed }
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.fixed.unwrap_or_else(Utc::now)
+    }
+
+    /// The `(now, timestamp)` pair the time adapter answers with: an ISO-8601 string with
+    /// milliseconds in our timezone (`Z` for UTC, as JavaScript's `toISOString`), and unix
+    /// seconds.
+    pub fn response(&self) -> (String, u64) {
+        let now = self.now();
+        let formatted = now
+            .with_timezone(&self.timezone)
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+        (formatted, now.timestamp() as u64)
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock {
+            timezone: Tz::UTC,
+            fixed: None,
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::env;
 
+pub mod clock;
+pub mod telemetry;
 pub mod unit;
 
 pub const GAS_LIMIT: i64 = 8000000;
@@ -100,6 +102,36 @@ impl Network {
         }
     }
 
+    pub fn multi_sig_wallet_address(&self) -> String {
+        match self {
+            Network::Ethereum => env::var("ETHEREUM_MULTI_SIG_WALLET_ADDRESS")
+                .expect("ETHEREUM_MULTI_SIG_WALLET_ADDRESS must be set"),
+            Network::Polygon => env::var("POLYGON_MULTI_SIG_WALLET_ADDRESS")
+                .expect("POLYGON_MULTI_SIG_WALLET_ADDRESS must be set"),
+            Network::Avalanche => env::var("AVALANCHE_MULTI_SIG_WALLET_ADDRESS")
+                .expect("AVALANCHE_MULTI_SIG_WALLET_ADDRESS must be set"),
+        }
+    }
+
+    pub fn oracle_address(&self) -> String {
+        match self {
+            Network::Ethereum => {
+                env::var("ETHEREUM_ORACLE_ADDRESS").expect("ETHEREUM_ORACLE_ADDRESS must be set")
+            }
+            Network::Polygon => unimplemented!(),
+            Network::Avalanche => unimplemented!(),
+        }
+    }
+
+    pub fn time_adapter_bridge_name(&self) -> String {
+        match self {
+            Network::Ethereum => env::var("ETHEREUM_TIME_ADAPTER_BRIDGE_NAME")
+                .expect("ETHEREUM_TIME_ADAPTER_BRIDGE_NAME must be set"),
+            Network::Polygon => unimplemented!(),
+            Network::Avalanche => unimplemented!(),
+        }
+    }
+
     pub fn meta_transactional_nft_721_address(&self) -> String {
         match self {
             Network::Ethereum => unimplemented!(),
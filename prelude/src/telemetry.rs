@@ -0,0 +1,29 @@
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::env;
+use std::net::SocketAddr;
+use tracing_subscriber::EnvFilter;
+
+/// Installs the global tracing subscriber. The filter is read from `RUST_LOG` and defaults to `info`.
+pub fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+}
+
+/// Serves Prometheus metrics on `METRICS_ADDR` (e.g. `0.0.0.0:9000`) when it is set.
+/// Must be called from within a tokio runtime.
+pub fn init_metrics() {
+    let addr = match env::var("METRICS_ADDR") {
+        Ok(addr) => addr,
+        Err(_) => return,
+    };
+    let addr = addr
+        .parse::<SocketAddr>()
+        .expect("METRICS_ADDR must be a socket address");
+
+    PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .install()
+        .expect("failed to install prometheus exporter");
+
+    tracing::info!(%addr, "serving prometheus metrics");
+}
@@ -1,53 +1,159 @@
-use bigdecimal::BigDecimal;
-use regex::Regex;
-use std::collections::HashMap;
-use std::ops::Mul;
+use bigdecimal::{BigDecimal, Signed};
+use primitive_types::U256;
 use std::str::FromStr;
 
-fn convert<'a>(value: &str, unit: &'a str) -> HashMap<&'a str, String> {
-    let v = to_ether(value, unit);
-    let mut map: HashMap<&'a str, String> = HashMap::new();
+#[derive(PartialEq, Clone, Debug, Copy, strum_macros::EnumString, strum_macros::Display)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum Unit {
+    Wei,
+    Kwei,
+    Gwei,
+    Szabo,
+    Finney,
+    Ether,
+}
 
-    map.insert(unit, BigDecimal::from_str(&value).unwrap().to_string());
+impl Unit {
+    pub fn decimals(&self) -> u32 {
+        match self {
+            Unit::Wei => 0,
+            Unit::Kwei => 3,
+            Unit::Gwei => 9,
+            Unit::Szabo => 12,
+            Unit::Finney => 15,
+            Unit::Ether => 18,
+        }
+    }
+}
 
-    if unit != "wei" {
-        map.insert("wei", s(&v, "1000000000000000000"));
+/// Converts a decimal string such as `"1.5"` into its base-unit integer with the given decimals.
+pub fn parse_units(value: &str, decimals: u32) -> UnitResult<U256> {
+    let v = BigDecimal::from_str(value.trim())
+        .map_err(|_| Error::Internal(format!("invalid number: {}", value)))?;
+    if v.is_negative() {
+        return Err(Error::Internal(format!("negative amount: {}", value)));
     }
-    if unit != "ether" {
-        map.insert("ether", s(&v, "1"));
+
+    let v = v * pow10(decimals);
+    if !v.is_integer() {
+        return Err(Error::Internal(format!(
+            "{} has more than {} decimal places",
+            value, decimals
+        )));
     }
 
-    return map;
+    U256::from_dec_str(&v.with_scale(0).to_string())
+        .map_err(|_| Error::Internal(format!("{} overflows uint256", value)))
+}
+
+/// Converts a base-unit integer into a decimal value with the given decimals.
+pub fn format_units(amount: U256, decimals: u32) -> BigDecimal {
+    let v = BigDecimal::from_str(&amount.to_string()).unwrap();
+    (v / pow10(decimals)).normalized()
 }
 
-fn m(v: &BigDecimal, u: &str) -> BigDecimal {
-    return v.mul(&BigDecimal::from_str(u).unwrap());
+pub fn to_wei(value: &str, unit: Unit) -> UnitResult<U256> {
+    parse_units(value, unit.decimals())
 }
 
-fn s(v: &BigDecimal, u: &str) -> String {
-    return t(v.mul(&BigDecimal::from_str(u).unwrap()).to_string());
+pub fn from_wei(wei: U256, unit: Unit) -> BigDecimal {
+    format_units(wei, unit.decimals())
 }
 
-fn t(v: String) -> String {
-    let re = Regex::new(r"(.*)\.0+$").unwrap();
-    let v = re.replace_all(&v, "$1").to_string();
-    let re = Regex::new(r"(.*\.\d+[1-9]+)(0+)$").unwrap();
-    return re.replace_all(&v, "$1").to_string();
+pub fn to_ether(value: &str, unit: Unit) -> UnitResult<BigDecimal> {
+    Ok(from_wei(to_wei(value, unit)?, Unit::Ether))
 }
 
-pub fn to_wei(value: &str, unit: &str) -> String {
-    return convert(&value, &unit).get("wei").unwrap().to_string();
+fn pow10(decimals: u32) -> BigDecimal {
+    BigDecimal::from_str(&format!("1e{}", decimals)).unwrap()
 }
 
-pub fn to_ether(value: &str, unit: &str) -> BigDecimal {
-    let v = BigDecimal::from_str(&value).unwrap();
+pub type UnitResult<T> = Result<T, Error>;
 
-    if unit == "wei" {
-        return m(&v, "0.000000000000000001");
+#[derive(thiserror::Error, Debug, PartialOrd, PartialEq, Clone)]
+pub enum Error {
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_decimal_values() {
+        assert_eq!(
+            to_wei("1.5", Unit::Ether).unwrap(),
+            U256::from(1_500_000_000_000_000_000u128)
+        );
+        assert_eq!(
+            to_wei("0.000000000000000001", Unit::Ether).unwrap(),
+            U256::one()
+        );
+        assert_eq!(
+            to_wei(" 2 ", Unit::Gwei).unwrap(),
+            U256::from(2_000_000_000u64)
+        );
+        assert_eq!(parse_units("0", 18).unwrap(), U256::zero());
     }
-    if unit == "ether" {
-        return m(&v, "1");
+
+    #[test]
+    fn accepts_trailing_zeros_beyond_decimals() {
+        assert_eq!(
+            to_wei("1.50000000000000000000", Unit::Ether).unwrap(),
+            U256::from(1_500_000_000_000_000_000u128)
+        );
+        assert_eq!(to_wei("3.000", Unit::Wei).unwrap(), U256::from(3));
     }
 
-    panic!("unit not supported");
+    #[test]
+    fn rejects_too_many_decimals_instead_of_rounding() {
+        assert!(to_wei("0.0000000000000000001", Unit::Ether).is_err());
+        assert!(to_wei("1.5", Unit::Wei).is_err());
+        assert!(parse_units("0.1234567", 6).is_err());
+    }
+
+    #[test]
+    fn rejects_negative_and_empty_input() {
+        assert!(to_wei("-1", Unit::Ether).is_err());
+        assert!(to_wei("-0.5", Unit::Ether).is_err());
+        assert!(to_wei("", Unit::Ether).is_err());
+        assert!(to_wei("   ", Unit::Ether).is_err());
+        assert!(to_wei("abc", Unit::Ether).is_err());
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        let max = U256::MAX.to_string();
+        assert!(parse_units(&max, 1).is_err());
+        assert!(parse_units(&format!("{}0", max), 0).is_err());
+    }
+
+    #[test]
+    fn formats_without_rounding() {
+        assert_eq!(
+            from_wei(U256::from(1_500_000_000_000_000_000u128), Unit::Ether).to_string(),
+            "1.5"
+        );
+        assert_eq!(
+            from_wei(U256::one(), Unit::Ether).to_string(),
+            "0.000000000000000001"
+        );
+        assert_eq!(format_units(U256::from(1234), 0).to_string(), "1234");
+        assert_eq!(format_units(U256::zero(), 18).to_string(), "0");
+    }
+
+    #[test]
+    fn round_trips_u256_max() {
+        for decimals in [0, 6, 18, 77] {
+            let formatted = format_units(U256::MAX, decimals).to_string();
+            assert_eq!(parse_units(&formatted, decimals).unwrap(), U256::MAX);
+        }
+    }
+
+    #[test]
+    fn converts_between_units() {
+        assert_eq!(to_ether("1500", Unit::Finney).unwrap().to_string(), "1.5");
+        assert_eq!("gwei".parse::<Unit>().unwrap(), Unit::Gwei);
+    }
 }
@@ -0,0 +1,120 @@
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use dotenv::dotenv;
+use prelude::clock::Clock;
+use prelude::telemetry;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+use std::net::SocketAddr;
+
+/// Chainlink external adapter request. The time adapter ignores everything but `id`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AdapterRequest {
+    id: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct AdapterResponse {
+    #[serde(rename = "jobRunID")]
+    job_run_id: Value,
+    data: TimeData,
+}
+
+#[derive(Debug, Serialize)]
+struct TimeData {
+    now: String,
+    timestamp: u64,
+}
+
+#[tokio::main]
+pub async fn main() {
+    dotenv().ok();
+    telemetry::init_tracing();
+    telemetry::init_metrics();
+
+    let addr = env::var("TIME_ADAPTER_ADDR")
+        .unwrap_or_else(|_| "0.0.0.0:8080".to_string())
+        .parse::<SocketAddr>()
+        .expect("TIME_ADAPTER_ADDR must be a socket address");
+    let clock = Clock::from_env();
+    tracing::info!(%addr, ?clock, "time adapter listening");
+
+    let app = Router::new().route("/", post(time)).with_state(clock);
+    if let Err(e) = axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await
+    {
+        tracing::error!("error: {:?}", e);
+    }
+}
+
+#[tracing::instrument(skip(clock))]
+async fn time(
+    State(clock): State<Clock>,
+    Json(req): Json<AdapterRequest>,
+) -> Json<AdapterResponse> {
+    let (now, timestamp) = clock.response();
+    tracing::info!(%now, timestamp, "answering");
+
+    Json(AdapterResponse {
+        job_run_id: if req.id.is_null() {
+            Value::from(0)
+        } else {
+            req.id
+        },
+        data: TimeData { now, timestamp },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use chrono_tz::Tz;
+    use serde_json::json;
+
+    fn clock(timezone: Tz) -> Clock {
+        Clock {
+            timezone,
+            fixed: Some(Utc.with_ymd_and_hms(2023, 5, 1, 3, 0, 0).unwrap()),
+        }
+    }
+
+    async fn answer(clock: Clock, body: Value) -> Value {
+        let req = serde_json::from_value::<AdapterRequest>(body).unwrap();
+        let Json(res) = time(State(clock), Json(req)).await;
+        serde_json::to_value(res).unwrap()
+    }
+
+    #[tokio::test]
+    async fn answers_with_the_fixed_time() {
+        let res = answer(
+            clock(Tz::UTC),
+            json!({"id": "278c97ffadb54a5bbb93cfec5f7b5503", "data": {}}),
+        )
+        .await;
+        assert_eq!(
+            res,
+            json!({
+                "jobRunID": "278c97ffadb54a5bbb93cfec5f7b5503",
+                "data": {"now": "2023-05-01T03:00:00.000Z", "timestamp": 1682910000u64},
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn renders_now_in_the_timezone() {
+        let res = answer(clock(Tz::Asia__Tokyo), json!({"id": 1})).await;
+        assert_eq!(res["data"]["now"], "2023-05-01T12:00:00.000+09:00");
+        assert_eq!(res["data"]["timestamp"], 1682910000u64);
+    }
+
+    #[tokio::test]
+    async fn defaults_a_missing_id() {
+        let res = answer(clock(Tz::UTC), json!({})).await;
+        assert_eq!(res["jobRunID"], 0);
+    }
+}